@@ -1,49 +1,35 @@
-const { execFile } = require("child_process");
-const fs = require("fs");
-const path = require("path");
+// elarocks query server (`cargo run --bin server`), keeps RocksDB open between queries
+const SERVER_URL = process.env.ELAROCKS_SERVER_URL || "http://127.0.0.1:8080";
 
-// Executes the Rust binary and returns the parsed data
-async function executeRustBinary(
+// Runs one paginated query on the server and returns its data
+// {cursor, node} per event then the page info
+async function querySelectServer(
     startKey,
     endKey,
     searchDirection,
     maxReturns,
     cursorValue,
-    imageContains,
-    pidMatch
+    filterParams,
+    totalCount = false
 ) {
-    return new Promise((resolve, reject) => {
-        const filePath = path.join(__dirname, "nano-select");
-
-        console.log("Executing:", filePath);
-
-        const args = [
-            startKey,
-            endKey,
-            searchDirection,
-            maxReturns,
-            cursorValue,
-            imageContains,
-            pidMatch,
-        ];
-
-        execFile(filePath, args, (error, stdout, stderr) => {
-            if (error) {
-                console.error("Command Error:", error);
-                console.error("Standard Error Output:", stderr);
-                reject(error);
-                return;
-            }
-
-            try {
-                const lines = stdout.trim().split("\n");
-                const data = lines.map((line) => JSON.parse(line));
-                resolve(data);
-            } catch (parseError) {
-                reject(parseError);
-            }
-        });
+    const params = new URLSearchParams({
+        start_key: startKey,
+        end_key: endKey,
+        direction: searchDirection,
+        count: String(maxReturns),
+        cursor: cursorValue,
+        ...filterParams,
     });
+    // counting is a pass over the whole window, only asked for when the query selects totalCount
+    if (totalCount) {
+        params.set("total_count", "true");
+    }
+    const response = await fetch(`${SERVER_URL}/select?${params}`);
+    const data = await response.json();
+    if (!response.ok) {
+        throw new Error(data.error || `query server returned ${response.status}`);
+    }
+    return data;
 }
 
 // GraphQL resolvers
@@ -55,7 +41,12 @@ const resolvers = {
             context,
             info
         ) => {
-            return fetchSysmonData(filter, "Registry value set", pagination);
+            return fetchSysmonData(
+                filter,
+                "Registry value set",
+                pagination,
+                info
+            );
         },
         ProcessCreateEve: async (
             parent,
@@ -63,7 +54,7 @@ const resolvers = {
             context,
             info
         ) => {
-            return fetchSysmonData(filter, "Process Create", pagination);
+            return fetchSysmonData(filter, "Process Create", pagination, info);
         },
         NetworkConnectionEve: async (
             parent,
@@ -74,14 +65,148 @@ const resolvers = {
             return fetchSysmonData(
                 filter,
                 "Network connection detected",
-                pagination
+                pagination,
+                info
+            );
+        },
+        DriverLoadedEve: async (
+            parent,
+            { filter, pagination },
+            context,
+            info
+        ) => {
+            return fetchSysmonData(filter, "Driver loaded", pagination, info);
+        },
+        CreateRemoteThreadEve: async (
+            parent,
+            { filter, pagination },
+            context,
+            info
+        ) => {
+            return fetchSysmonData(
+                filter,
+                "CreateRemoteThread detected",
+                pagination,
+                info
+            );
+        },
+        SysmonConfigStateChangedEve: async (
+            parent,
+            { filter, pagination },
+            context,
+            info
+        ) => {
+            return fetchSysmonData(
+                filter,
+                "Sysmon config state changed",
+                pagination,
+                info
+            );
+        },
+        WmiEventFilterEve: async (
+            parent,
+            { filter, pagination },
+            context,
+            info
+        ) => {
+            return fetchSysmonData(
+                filter,
+                "WmiEventFilter activity detected",
+                pagination,
+                info
+            );
+        },
+        WmiEventConsumerEve: async (
+            parent,
+            { filter, pagination },
+            context,
+            info
+        ) => {
+            return fetchSysmonData(
+                filter,
+                "WmiEventConsumer activity detected",
+                pagination,
+                info
+            );
+        },
+        WmiEventConsumerToFilterEve: async (
+            parent,
+            { filter, pagination },
+            context,
+            info
+        ) => {
+            return fetchSysmonData(
+                filter,
+                "WmiEventConsumerToFilter activity detected",
+                pagination,
+                info
+            );
+        },
+        ClipboardChangedEve: async (
+            parent,
+            { filter, pagination },
+            context,
+            info
+        ) => {
+            return fetchSysmonData(
+                filter,
+                "Clipboard changed",
+                pagination,
+                info
+            );
+        },
+        FileBlockExecutableEve: async (
+            parent,
+            { filter, pagination },
+            context,
+            info
+        ) => {
+            return fetchSysmonData(
+                filter,
+                "File Block Executable",
+                pagination,
+                info
+            );
+        },
+        FileBlockShreddingEve: async (
+            parent,
+            { filter, pagination },
+            context,
+            info
+        ) => {
+            return fetchSysmonData(
+                filter,
+                "File Block Shredding",
+                pagination,
+                info
+            );
+        },
+        FileExecutableDetectedEve: async (
+            parent,
+            { filter, pagination },
+            context,
+            info
+        ) => {
+            return fetchSysmonData(
+                filter,
+                "File Executable Detected",
+                pagination,
+                info
             );
         },
     },
 };
 
+// the query selects this field of the connection
+function selectsField(info, name) {
+    const selectionSet = info.fieldNodes[0].selectionSet;
+    return (selectionSet ? selectionSet.selections : []).some(
+        (selection) => selection.name && selection.name.value === name
+    );
+}
+
 // Fetches Sysmon data using the Rust binary
-async function fetchSysmonData(filter, eventType, pagination) {
+async function fetchSysmonData(filter, eventType, pagination, info) {
     start_time = filter.datetime.start.replace("T", " ").replace("Z", "00000");
     end_time = filter.datetime.end.replace("T", " ").replace("Z", "99999");
 
@@ -100,8 +225,20 @@ async function fetchSysmonData(filter, eventType, pagination) {
         maxReturns = pagination.last;
     }
 
-    let imageContains = filter.image || "";
-    let pidMatch = filter.process_id;
+    // server filter parameters, "" is no filter, exact filters use secondary indexes
+    const filterParams = {
+        image: filter.image || "",
+        process_id: filter.process_id != null ? String(filter.process_id) : "",
+        process_guid: filter.process_guid || "",
+        parent_process_guid: filter.parent_process_guid || "",
+        agent_id: filter.agent_id || "",
+        user: filter.user || "",
+        destination_ip: filter.destination_ip || "",
+        destination_port: filter.destination_port || "",
+        destination_hostname: filter.destination_hostname || "",
+        query_name: filter.query_name || "",
+        hash: filter.hash || "",
+    };
 
     // first-after, last-before combination
     const cursorValue = pagination.after || pagination.before || "";
@@ -110,34 +247,33 @@ async function fetchSysmonData(filter, eventType, pagination) {
         let cursorForOffset = cursorValue;
         if (pagination.offset) {
             // Fetch the cursor corresponding to the offset position
-            const offsetData = await executeRustBinary(
+            const offsetData = await querySelectServer(
                 startKey,
                 endKey,
                 searchDirection,
                 pagination.offset, // offset to maxValue search
                 "", // cursor must be empty
-                imageContains,
-                pidMatch
+                filterParams
             );
 
-            const offsetDataReturn = offsetData[0];
-            const offsetPageInfoDataReturn =
-                offsetDataReturn[offsetDataReturn.length - 1];
-            // real-data cursor set
-            cursorForOffset = offsetPageInfoDataReturn.end_cursor;
+            const offsetPageInfoDataReturn = offsetData[offsetData.length - 1];
+            // real-data cursor set, pages are oldest first: last/before continues from the oldest skipped event
+            cursorForOffset =
+                searchDirection === "last"
+                    ? offsetPageInfoDataReturn.start_cursor
+                    : offsetPageInfoDataReturn.end_cursor;
         }
 
-        const rawData = await executeRustBinary(
+        const data = await querySelectServer(
             startKey,
             endKey,
             searchDirection,
             maxReturns,
             cursorForOffset,
-            imageContains,
-            pidMatch
+            filterParams,
+            selectsField(info, "totalCount")
         );
 
-        const data = rawData[0];
         const pageInfoData = data[data.length - 1];
 
         // Extract the edges (all elements except the last one)
@@ -1,6 +1,14 @@
 const { gql } = require("apollo-server");
 const typeDefs = gql`
     # query type
+    # Sysmon Hashes by algorithm, null when the config does not compute it
+    type Hashes {
+        sha1: String
+        md5: String
+        sha256: String
+        imphash: String
+    }
+
     type ProcessCreateEve {
         agent_name: String!
         agent_id: String!
@@ -20,7 +28,7 @@ const typeDefs = gql`
         logon_id: Int!
         terminal_session_id: Int!
         integrity_level: String!
-        hashes: [String!]
+        hashes: Hashes!
         parent_process_guid: String!
         parent_process_id: Int!
         parent_image: String!
@@ -65,6 +73,139 @@ const typeDefs = gql`
         destination_port_name: String!
     }
 
+    type DriverLoadedEve {
+        agent_name: String!
+        agent_id: String!
+        event_action: String!
+        utc_time: String!
+        image_loaded: String!
+        hashes: String!
+        signed: Boolean!
+        signature: String!
+        signature_status: String!
+    }
+
+    type CreateRemoteThreadEve {
+        agent_name: String!
+        agent_id: String!
+        event_action: String!
+        utc_time: String!
+        source_process_guid: String!
+        source_process_id: Int!
+        source_image: String!
+        target_process_guid: String!
+        target_process_id: Int!
+        target_image: String!
+        new_thread_id: Int!
+        start_address: String!
+        start_module: String!
+        start_function: String!
+        source_user: String!
+        target_user: String!
+    }
+
+    type SysmonConfigStateChangedEve {
+        agent_name: String!
+        agent_id: String!
+        event_action: String!
+        utc_time: String!
+        configuration: String!
+        configuration_file_hash: String!
+    }
+
+    type WmiEventFilterEve {
+        agent_name: String!
+        agent_id: String!
+        event_action: String!
+        utc_time: String!
+        event_type: String!
+        operation: String!
+        user: String!
+        event_namespace: String!
+        name: String!
+        query: String!
+    }
+
+    type WmiEventConsumerEve {
+        agent_name: String!
+        agent_id: String!
+        event_action: String!
+        utc_time: String!
+        event_type: String!
+        operation: String!
+        user: String!
+        name: String!
+        consumer_type: String!
+        destination: String!
+    }
+
+    type WmiEventConsumerToFilterEve {
+        agent_name: String!
+        agent_id: String!
+        event_action: String!
+        utc_time: String!
+        event_type: String!
+        operation: String!
+        user: String!
+        consumer: String!
+        filter: String!
+    }
+
+    type ClipboardChangedEve {
+        agent_name: String!
+        agent_id: String!
+        event_action: String!
+        utc_time: String!
+        process_guid: String!
+        process_id: Int!
+        image: String!
+        session: Int!
+        client_info: String!
+        hashes: String!
+        archived: Boolean!
+        user: String!
+    }
+
+    type FileBlockExecutableEve {
+        agent_name: String!
+        agent_id: String!
+        event_action: String!
+        utc_time: String!
+        process_guid: String!
+        process_id: Int!
+        user: String!
+        image: String!
+        target_filename: String!
+        hashes: String!
+    }
+
+    type FileBlockShreddingEve {
+        agent_name: String!
+        agent_id: String!
+        event_action: String!
+        utc_time: String!
+        process_guid: String!
+        process_id: Int!
+        user: String!
+        image: String!
+        target_filename: String!
+        hashes: String!
+        is_executable: Boolean!
+    }
+
+    type FileExecutableDetectedEve {
+        agent_name: String!
+        agent_id: String!
+        event_action: String!
+        utc_time: String!
+        process_guid: String!
+        process_id: Int!
+        user: String!
+        image: String!
+        target_filename: String!
+        hashes: String!
+    }
+
     # input filter
     input DateTimeRange {
         start: String!
@@ -74,9 +215,19 @@ const typeDefs = gql`
     input SysmonFilter {
         datetime: DateTimeRange!
         process_id: Int
-        # user: String
-        # agent_id: String
+        process_guid: String
+        parent_process_guid: String
+        user: String
+        agent_id: String
         image: String
+        # ip or CIDR block ("10.0.0.0/8", "fe80::/10")
+        destination_ip: String
+        # port or range ("443", "1024-2048")
+        destination_port: String
+        destination_hostname: String
+        query_name: String
+        # process create / image loaded, "SHA256=...", "IMPHASH=..."
+        hash: String
     }
 
     # node with edges, pagination(cursor based)
@@ -120,6 +271,116 @@ const typeDefs = gql`
         node: NetworkConnectionEve!
     }
 
+    type DriverLoadedEveConnection {
+        edges: [DriverLoadedEveEdge!]
+        pageInfo: PageInfo!
+        totalCount: Int
+    }
+
+    type DriverLoadedEveEdge {
+        cursor: String!
+        node: DriverLoadedEve!
+    }
+
+    type CreateRemoteThreadEveConnection {
+        edges: [CreateRemoteThreadEveEdge!]
+        pageInfo: PageInfo!
+        totalCount: Int
+    }
+
+    type CreateRemoteThreadEveEdge {
+        cursor: String!
+        node: CreateRemoteThreadEve!
+    }
+
+    type SysmonConfigStateChangedEveConnection {
+        edges: [SysmonConfigStateChangedEveEdge!]
+        pageInfo: PageInfo!
+        totalCount: Int
+    }
+
+    type SysmonConfigStateChangedEveEdge {
+        cursor: String!
+        node: SysmonConfigStateChangedEve!
+    }
+
+    type WmiEventFilterEveConnection {
+        edges: [WmiEventFilterEveEdge!]
+        pageInfo: PageInfo!
+        totalCount: Int
+    }
+
+    type WmiEventFilterEveEdge {
+        cursor: String!
+        node: WmiEventFilterEve!
+    }
+
+    type WmiEventConsumerEveConnection {
+        edges: [WmiEventConsumerEveEdge!]
+        pageInfo: PageInfo!
+        totalCount: Int
+    }
+
+    type WmiEventConsumerEveEdge {
+        cursor: String!
+        node: WmiEventConsumerEve!
+    }
+
+    type WmiEventConsumerToFilterEveConnection {
+        edges: [WmiEventConsumerToFilterEveEdge!]
+        pageInfo: PageInfo!
+        totalCount: Int
+    }
+
+    type WmiEventConsumerToFilterEveEdge {
+        cursor: String!
+        node: WmiEventConsumerToFilterEve!
+    }
+
+    type ClipboardChangedEveConnection {
+        edges: [ClipboardChangedEveEdge!]
+        pageInfo: PageInfo!
+        totalCount: Int
+    }
+
+    type ClipboardChangedEveEdge {
+        cursor: String!
+        node: ClipboardChangedEve!
+    }
+
+    type FileBlockExecutableEveConnection {
+        edges: [FileBlockExecutableEveEdge!]
+        pageInfo: PageInfo!
+        totalCount: Int
+    }
+
+    type FileBlockExecutableEveEdge {
+        cursor: String!
+        node: FileBlockExecutableEve!
+    }
+
+    type FileBlockShreddingEveConnection {
+        edges: [FileBlockShreddingEveEdge!]
+        pageInfo: PageInfo!
+        totalCount: Int
+    }
+
+    type FileBlockShreddingEveEdge {
+        cursor: String!
+        node: FileBlockShreddingEve!
+    }
+
+    type FileExecutableDetectedEveConnection {
+        edges: [FileExecutableDetectedEveEdge!]
+        pageInfo: PageInfo!
+        totalCount: Int
+    }
+
+    type FileExecutableDetectedEveEdge {
+        cursor: String!
+        node: FileExecutableDetectedEve!
+    }
+
     input PaginationInput {
         first: Int
         last: Int
@@ -141,6 +402,46 @@ const typeDefs = gql`
             filter: SysmonFilter!
             pagination: PaginationInput
         ): NetworkConnectionEveConnection
+        DriverLoadedEve(
+            filter: SysmonFilter!
+            pagination: PaginationInput
+        ): DriverLoadedEveConnection
+        CreateRemoteThreadEve(
+            filter: SysmonFilter!
+            pagination: PaginationInput
+        ): CreateRemoteThreadEveConnection
+        SysmonConfigStateChangedEve(
+            filter: SysmonFilter!
+            pagination: PaginationInput
+        ): SysmonConfigStateChangedEveConnection
+        WmiEventFilterEve(
+            filter: SysmonFilter!
+            pagination: PaginationInput
+        ): WmiEventFilterEveConnection
+        WmiEventConsumerEve(
+            filter: SysmonFilter!
+            pagination: PaginationInput
+        ): WmiEventConsumerEveConnection
+        WmiEventConsumerToFilterEve(
+            filter: SysmonFilter!
+            pagination: PaginationInput
+        ): WmiEventConsumerToFilterEveConnection
+        ClipboardChangedEve(
+            filter: SysmonFilter!
+            pagination: PaginationInput
+        ): ClipboardChangedEveConnection
+        FileBlockExecutableEve(
+            filter: SysmonFilter!
+            pagination: PaginationInput
+        ): FileBlockExecutableEveConnection
+        FileBlockShreddingEve(
+            filter: SysmonFilter!
+            pagination: PaginationInput
+        ): FileBlockShreddingEveConnection
+        FileExecutableDetectedEve(
+            filter: SysmonFilter!
+            pagination: PaginationInput
+        ): FileExecutableDetectedEveConnection
     }
 `;
 
@@ -17,9 +17,12 @@ async function executeRustBinary(
 
         console.log("Executing:", filePath);
 
+        // positional arguments, the compatibility mode of nano-select ("" is unset)
         execFile(
             filePath,
             [
+                "--format",
+                "ndjson",
                 startKey,
                 endKey,
                 searchDirection,
@@ -35,6 +38,7 @@ async function executeRustBinary(
                 }
 
                 try {
+                    // one {cursor, node} per line, then {page_info}
                     const lines = stdout.trim().split("\n");
                     const data = lines.map((line) => JSON.parse(line));
                     resolve(data);
@@ -11,7 +11,7 @@ mod envs;
 mod structs;
 
 // use Imports
-use envs::env::*;
+use envs::config::{Config, Needs};
 use envs::byEventsEnv::*;
 use structs::events::Event15;
 
@@ -89,10 +89,21 @@ fn write_to_csv(entries: Vec<Event15>, filename: &str) -> std::io::Result<()> {
 
 #[tokio::main]
 async fn main() {
-    match fetch_data_from_es().await {
+    let config = match Config::from_args(std::env::args(), Needs::Fetch) {
+        Ok((config, _)) => config,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return;
+        }
+    };
+
+    match fetch_data_from_es(&config.elasticsearch).await {
         Ok(data) => {
             let entries = parse_output(&data);
-            let filenames = format!("{}{}{}", SAVELOCATION, EVENT_CODE, CSVNAME);
+            let filenames = format!(
+                "{}{}{}",
+                config.csv.save_location, EVENT_CODE, config.csv.csv_name
+            );
             if let Err(e) = write_to_csv(entries, &filenames) {
                 eprintln!("Error writing to CSV: {:?}", e);
             }
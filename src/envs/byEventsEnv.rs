@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use super::env::*;
+use super::config::ElasticsearchConfig;
 use base64::{engine::general_purpose, Engine as _};
 use reqwest::header;
 use serde_json::json;
@@ -13,14 +13,14 @@ const EVE_CODE: &str = "13";
 // const TIMESTAMP_START: &str = "2023-08-01T00:00:00.000Z";
 // const TIMESTAMP_END: &str = "2023-08-01T00:00:00.000Z";
 
-pub fn build_query() -> serde_json::Value {
+pub fn build_query(es: &ElasticsearchConfig) -> serde_json::Value {
     json!({
         "query": {
             "bool": {
                 "must": [
                     { "term": {"event.code": EVE_CODE} },
                     { "term": {"event.module": "sysmon"} },
-                    { "range": {"@timestamp": {"gt": TIMESTAMP_START, "lt": TIMESTAMP_END}} },
+                    { "range": {"@timestamp": {"gt": es.timestamp_start, "lt": es.timestamp_end.as_deref().unwrap_or("now")}} },
                     // Used instead of wildcard when message's type is "match_only_text"
                     // { "query_string": {
                     //     "fields": ["message"],
@@ -50,12 +50,12 @@ pub fn build_query() -> serde_json::Value {
                 ]
             }
         },
-        "size": SIZE
+        "size": es.size
     })
 }
 
-pub fn build_client() -> Result<reqwest::Client, reqwest::Error> {
-    let auth_value = format!("{}:{}", ID, PW);
+pub fn build_client(es: &ElasticsearchConfig) -> Result<reqwest::Client, reqwest::Error> {
+    let auth_value = format!("{}:{}", es.username, es.password);
     let auth_value_bytes = auth_value.as_bytes();
     let encoded: String = general_purpose::STANDARD_NO_PAD.encode(auth_value_bytes);
     let basic_auth_header = format!("Basic {}", encoded);
@@ -74,16 +74,19 @@ pub fn build_client() -> Result<reqwest::Client, reqwest::Error> {
 }
 
 pub async fn send_request(
+    es: &ElasticsearchConfig,
     client: &reqwest::Client,
     query: &serde_json::Value,
 ) -> Result<serde_json::Value, reqwest::Error> {
-    let url = format!("{}/{}/_search", ES_URL, INDEX);
+    let url = format!("{}/{}/_search", es.url, INDEX);
     let response = client.post(&url).json(query).send().await?;
     response.json().await
 }
 
-pub async fn fetch_data_from_es() -> Result<serde_json::Value, reqwest::Error> {
-    let client = build_client()?;
-    let query = build_query();
-    send_request(&client, &query).await
+pub async fn fetch_data_from_es(
+    es: &ElasticsearchConfig,
+) -> Result<serde_json::Value, reqwest::Error> {
+    let client = build_client(es)?;
+    let query = build_query(es);
+    send_request(es, &client, &query).await
 }
@@ -0,0 +1,289 @@
+// Command line of `select` / `nano-select`
+//
+// named flags: --event "Process Create" --from -2h [--to now] [--limit 100] [--after <cursor> | --last [--before <cursor>]]
+//              [--image-contains ...] [--pid ...] [--user ...] ... [--format json|ndjson|csv|table] [--total-count]
+// positional (compatibility, when the first argument is not a flag, --format and --total-count are still read), "" is unset:
+//   <start_key> <end_key> <first|last> <count> <cursor> <image contains> <process_id> <process_guid> <parent_process_guid>
+//   <agent_id> <user> <destination_ip> <destination_port> <destination_hostname> <query_name> <hash>
+// `--config` / `--set` are taken out before (`Config::from_args`)
+use crate::storage::keys::SEARCH_TIME_FORMAT;
+use crate::storage::output::{take_format, OutputFormat};
+use crate::storage::pagination::Pagination;
+use crate::storage::query::{FilterArgs, Query};
+use crate::structs::events::EVENT_TYPES;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use clap::Parser;
+use std::error::Error;
+
+// events of a page when --limit is not given
+const DEFAULT_LIMIT: usize = 100;
+
+#[derive(Parser)]
+#[command(
+    about = "Print one page of stored events of one type",
+    after_help = "times: RFC 3339 (2023-07-01T09:00:00+09:00), \"2023-07-01 00:00:00.000\" (UTC), 2023-07-01, now, \
+                  or relative to now: -30s, -15m, -2h, -7d, -1w\n\
+                  --config <path> and --set <section.key=value> are read as in every binary (elarocks.example.toml)\n\
+                  positional arguments (<start_key> <end_key> <first|last> <count> <cursor> <image> <process_id> ...) \
+                  are still accepted when the first argument is not a flag"
+)]
+pub struct SelectArgs {
+    #[arg(
+        long,
+        help = "event.action (\"Process Create\", any case) or Sysmon event id (1)"
+    )]
+    event: String,
+    #[arg(long, allow_hyphen_values = true, value_parser = parse_time, help = "start of the window")]
+    from: DateTime<Utc>,
+    #[arg(
+        long,
+        allow_hyphen_values = true,
+        value_parser = parse_time,
+        default_value = "now",
+        help = "end of the window, included"
+    )]
+    to: DateTime<Utc>,
+    #[arg(long, default_value_t = DEFAULT_LIMIT, help = "events of the page")]
+    limit: usize,
+    #[arg(long, conflicts_with_all = ["before", "last"], help = "events after this cursor")]
+    after: Option<String>,
+    #[arg(long, help = "events before this cursor (implies --last)")]
+    before: Option<String>,
+    #[arg(long, help = "newest events of the window (or before --before)")]
+    last: bool,
+    #[arg(long, help = "image path contains")]
+    image_contains: Option<String>,
+    #[arg(long, help = "process id")]
+    pid: Option<u32>,
+    #[arg(long, help = "process guid, exact")]
+    process_guid: Option<String>,
+    #[arg(long, help = "parent process guid, exact")]
+    parent_process_guid: Option<String>,
+    #[arg(long, help = "winlogbeat agent id, exact")]
+    agent_id: Option<String>,
+    #[arg(long, help = "user (DOMAIN\\name), exact")]
+    user: Option<String>,
+    #[arg(long, help = "ip or CIDR block (10.0.0.0/8, fe80::/10)")]
+    destination_ip: Option<String>,
+    #[arg(long, help = "port or range (1024-2048)")]
+    destination_port: Option<String>,
+    #[arg(long, help = "host name, any case")]
+    destination_hostname: Option<String>,
+    #[arg(long, help = "DNS query name, any case")]
+    query_name: Option<String>,
+    #[arg(long, help = "SHA256=..., SHA1=..., MD5=... or IMPHASH=...")]
+    hash: Option<String>,
+    #[arg(long, default_value = "json", help = "json, ndjson, csv or table")]
+    format: OutputFormat,
+    #[arg(
+        long,
+        help = "count the matching events of the whole window (page_info.total_count), reads all of them"
+    )]
+    total_count: bool,
+}
+
+// What the select binaries run
+pub struct Search {
+    pub query: Query,
+    pub pagination: Pagination,
+    pub format: OutputFormat,
+    // page_info.total_count, a second pass over the window
+    pub total_count: bool,
+}
+
+// "-2h" → 2 hours
+fn parse_ago(ago: &str) -> Result<TimeDelta, String> {
+    let split = ago
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in -{} (s, m, h, d or w)", ago))?;
+    let (amount, unit) = ago.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("invalid relative time -{}", ago))?;
+    let delta = match unit {
+        "s" => TimeDelta::try_seconds(amount),
+        "m" => TimeDelta::try_minutes(amount),
+        "h" => TimeDelta::try_hours(amount),
+        "d" => TimeDelta::try_days(amount),
+        "w" => TimeDelta::try_weeks(amount),
+        _ => {
+            return Err(format!(
+                "unknown unit {:?} in -{} (s, m, h, d or w)",
+                unit, ago
+            ))
+        }
+    };
+    delta.ok_or_else(|| format!("relative time out of range: -{}", ago))
+}
+
+pub fn parse_time(text: &str) -> Result<DateTime<Utc>, String> {
+    let text = text.trim();
+    if text == "now" {
+        return Ok(Utc::now());
+    }
+    if let Some(ago) = text.strip_prefix('-') {
+        return Utc::now()
+            .checked_sub_signed(parse_ago(ago)?)
+            .ok_or_else(|| format!("relative time out of range: {}", text));
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    if let Ok(time) = NaiveDateTime::parse_from_str(text, SEARCH_TIME_FORMAT) {
+        return Ok(Utc.from_utc_datetime(&time));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Ok(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default()));
+    }
+    Err(format!("invalid time {:?}", text))
+}
+
+// event.action in any case, or the event code
+fn event_action(event: &str) -> Result<&'static str, String> {
+    EVENT_TYPES
+        .iter()
+        .find(|(code, name)| name.eq_ignore_ascii_case(event) || code.to_string() == event)
+        .map(|(_, name)| *name)
+        .ok_or_else(|| format!("unknown event {:?}", event))
+}
+
+fn nanos(time: DateTime<Utc>) -> Result<i64, String> {
+    time.timestamp_nanos_opt()
+        .ok_or_else(|| format!("time out of range: {}", time))
+}
+
+impl SelectArgs {
+    fn search(self) -> Result<Search, Box<dyn Error>> {
+        let filters = FilterArgs {
+            image: self.image_contains,
+            process_id: self.pid.map(|pid| pid.to_string()),
+            process_guid: self.process_guid,
+            parent_process_guid: self.parent_process_guid,
+            agent_id: self.agent_id,
+            user: self.user,
+            destination_ip: self.destination_ip,
+            destination_port: self.destination_port,
+            destination_hostname: self.destination_hostname,
+            query_name: self.query_name,
+            hash: self.hash,
+        }
+        .filters()?;
+        let query = Query::new(
+            event_action(&self.event)?,
+            nanos(self.from)?,
+            nanos(self.to)?,
+        )?
+        .filter(filters);
+        let pagination = if self.last || self.before.is_some() {
+            Pagination::new(
+                None,
+                None,
+                Some(self.limit),
+                self.before.as_deref(),
+                self.limit,
+            )?
+        } else {
+            Pagination::new(
+                Some(self.limit),
+                self.after.as_deref(),
+                None,
+                None,
+                self.limit,
+            )?
+        };
+        Ok(Search {
+            query,
+            pagination,
+            format: self.format,
+            total_count: self.total_count,
+        })
+    }
+}
+
+// the positional arguments, missing or "" is unset
+fn positional(
+    args: &[String],
+    format: OutputFormat,
+    total_count: bool,
+) -> Result<Search, Box<dyn Error>> {
+    if args.len() < 3 {
+        return Err("Please provide start_key and end_key as arguments".into());
+    }
+    let max_print_count = args
+        .get(4)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(usize::MAX);
+    // 'first' reads forward from the cursor (or the start), 'last' backward from it (or the end)
+    let pagination = Pagination::from_direction(
+        args.get(3).map(String::as_str),
+        max_print_count,
+        args.get(5).map(String::as_str),
+    )?;
+    let arg = |index: usize| args.get(index).cloned();
+    let filters = FilterArgs {
+        image: arg(6),
+        process_id: arg(7),
+        process_guid: arg(8),
+        parent_process_guid: arg(9),
+        agent_id: arg(10),
+        user: arg(11),
+        destination_ip: arg(12),
+        destination_port: arg(13),
+        destination_hostname: arg(14),
+        query_name: arg(15),
+        hash: arg(16),
+    }
+    .filters()?;
+    let query = Query::from_search_keys(&args[1], &args[2])?.filter(filters);
+    Ok(Search {
+        query,
+        pagination,
+        format,
+        total_count,
+    })
+}
+
+// program name first, like `env::args()`; --help and flag errors exit with clap's message
+pub fn parse_search(args: Vec<String>) -> Result<Search, Box<dyn Error>> {
+    let (format, mut rest) = take_format(args.clone())?;
+    // --total-count anywhere, like --format, positional arguments keep their index
+    let total_count = rest.iter().any(|arg| arg == "--total-count");
+    rest.retain(|arg| arg != "--total-count");
+    if rest.get(1).is_some_and(|first| !first.starts_with('-')) {
+        return positional(&rest, format, total_count);
+    }
+    SelectArgs::parse_from(args).search()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_times() {
+        let before = Utc::now();
+        let time = parse_time("-2h").unwrap();
+        let after = Utc::now();
+        assert!(before - TimeDelta::hours(2) <= time && time <= after - TimeDelta::hours(2));
+        assert!(parse_time("-2").is_err());
+        assert!(parse_time("-2y").is_err());
+    }
+
+    #[test]
+    fn out_of_range_relative_times_are_errors() {
+        for text in [
+            "-99999999999999h",
+            "-9223372036854775807s",
+            "-99999999999999w",
+            "-260000000w",
+        ] {
+            assert!(parse_time(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn out_of_range_flags_are_clap_errors() {
+        let args = ["select", "--event", "1", "--from", "-99999999999999h"];
+        assert!(SelectArgs::try_parse_from(args).is_err());
+    }
+}
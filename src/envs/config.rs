@@ -0,0 +1,525 @@
+use crate::storage::values::ValueCodec;
+use chrono::DateTime;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::{env, fmt, fs, io};
+use toml::{Table, Value};
+
+// Runtime settings shared by `main`, `rocks`, `select`, `nano-select`, `migrate` and `server`
+// read from TOML file, then overridden by environment variables, then by command line
+//
+// file location : `--config <path>` > ELAROCKS_CONFIG > ./elarocks.toml
+// env override  : ELAROCKS_<SECTION>_<KEY>, e.g. ELAROCKS_ELASTICSEARCH_PASSWORD
+// cli override  : `--set <section>.<key>=<value>`, e.g. --set elasticsearch.size=500
+// list values (indices) are comma separated when given as override
+pub const DEFAULT_CONFIG_PATH: &str = "elarocks.toml";
+const CONFIG_PATH_ENV: &str = "ELAROCKS_CONFIG";
+const ENV_PREFIX: &str = "ELAROCKS_";
+
+// every key accepted in the file, anything else is reported as unknown
+const KNOWN_KEYS: [&str; 18] = [
+    "elasticsearch.url",
+    "elasticsearch.username",
+    "elasticsearch.password",
+    "elasticsearch.indices",
+    "elasticsearch.timestamp_start",
+    "elasticsearch.timestamp_end",
+    "elasticsearch.size",
+    "elasticsearch.pit_keep_alive",
+    "elasticsearch.state_file",
+    "csv.save_location",
+    "csv.csv_name",
+    "rocksdb.db_location",
+    "rocksdb.csv_location",
+    "rocksdb.value_codec",
+    "rocksdb.batch_size",
+    "output.csv",
+    "output.rocksdb",
+    "server.listen",
+];
+
+// Sections a binary reads, required keys of the other sections may be left out
+// (`select` runs without elasticsearch credentials), they are then empty and not validated
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Needs {
+    // main: elasticsearch, csv and output
+    Fetch,
+    // rocks: rocksdb.csv_location
+    Load,
+    // select, nano-select, migrate, server: rocksdb.db_location only
+    Read,
+}
+
+pub struct Config {
+    pub elasticsearch: ElasticsearchConfig,
+    pub csv: CsvFileConfig,
+    pub rocksdb: RocksDbConfig,
+    pub output: OutputConfig,
+    pub server: ServerConfig,
+}
+
+pub struct ElasticsearchConfig {
+    // Elasticsearch url with scheme, e.g. https://localhost:9200
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    // When the CSV is saved, the file is created from the first index and rows of the next indices are appended
+    pub indices: Vec<String>,
+    // Search start and end timestamp (RFC 3339)
+    // without end timestamp every run fetches up to "now"
+    pub timestamp_start: String,
+    pub timestamp_end: Option<String>,
+    // Query size, number of hits fetched per page
+    pub size: usize,
+    // How long elasticsearch keeps point-in-time alive between two pages
+    pub pit_keep_alive: String,
+    // JSON file keeping the last written hit per index and event code, next run resumes from it
+    pub state_file: String,
+}
+
+pub struct CsvFileConfig {
+    // between save_location and csv_name event code will automatically generated
+    pub save_location: String,
+    pub csv_name: String,
+}
+
+pub struct RocksDbConfig {
+    pub db_location: String,
+    // directory of event{code}_logs.csv files read by `rocks`
+    pub csv_location: String,
+    // format of written values, "msgpack" or "cbor" (readers take every format, see storage/values.rs)
+    pub value_codec: ValueCodec,
+    // CSV rows written per WriteBatch by `rocks`, the resume offset moves after every batch
+    pub batch_size: usize,
+}
+
+// Where `main` writes fetched events
+// rocksdb writes typed events straight into rocksdb.db_location (same keys as `rocks`), csv is then only a debugging copy
+pub struct OutputConfig {
+    pub csv: bool,
+    pub rocksdb: bool,
+}
+
+// Query server (`server`), reads rocksdb.db_location while the loaders write it
+pub struct ServerConfig {
+    // address and port of the HTTP listener, e.g. 127.0.0.1:8080
+    pub listen: String,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read {
+        path: String,
+        source: io::Error,
+    },
+    Syntax {
+        path: String,
+        message: String,
+    },
+    Usage(String),
+    UnknownKey {
+        key: String,
+    },
+    Missing {
+        key: String,
+    },
+    Invalid {
+        key: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read { path, source } => {
+                write!(f, "cannot read config file {}: {}", path, source)
+            }
+            ConfigError::Syntax { path, message } => {
+                write!(f, "invalid TOML in {}: {}", path, message)
+            }
+            ConfigError::Usage(message) => write!(f, "{}", message),
+            ConfigError::UnknownKey { key } => write!(f, "config key `{}`: unknown key", key),
+            ConfigError::Missing { key } => write!(
+                f,
+                "config key `{}`: missing (set it in the file, {} or --set {}=...)",
+                key,
+                env_name(key),
+                key
+            ),
+            ConfigError::Invalid { key, value, reason } => {
+                write!(
+                    f,
+                    "config key `{}`: invalid value {}, {}",
+                    key, value, reason
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// Where a setting came from, file values keep their TOML type
+enum Raw<'a> {
+    File(&'a Value),
+    Override(String),
+}
+
+struct Sources {
+    table: Table,
+    cli: HashMap<String, String>,
+}
+
+impl Sources {
+    fn raw(&self, key: &str) -> Option<Raw<'_>> {
+        if let Some(value) = self.cli.get(key) {
+            return Some(Raw::Override(value.clone()));
+        }
+        if let Ok(value) = env::var(env_name(key)) {
+            return Some(Raw::Override(value));
+        }
+        let (section, name) = key.split_once('.')?;
+        self.table
+            .get(section)
+            .and_then(Value::as_table)
+            .and_then(|section| section.get(name))
+            .map(Raw::File)
+    }
+
+    fn string(&self, key: &str, default: Option<&str>) -> Result<String, ConfigError> {
+        match self.raw(key) {
+            Some(Raw::Override(value)) => Ok(value),
+            Some(Raw::File(Value::String(value))) => Ok(value.clone()),
+            Some(Raw::File(value)) => Err(invalid(key, value, "expected a string")),
+            None => default
+                .map(str::to_string)
+                .ok_or_else(|| ConfigError::Missing {
+                    key: key.to_string(),
+                }),
+        }
+    }
+
+    fn optional_string(&self, key: &str) -> Result<Option<String>, ConfigError> {
+        match self.raw(key) {
+            None => Ok(None),
+            Some(_) => self.string(key, None).map(Some),
+        }
+    }
+
+    fn usize(&self, key: &str, default: Option<usize>) -> Result<usize, ConfigError> {
+        let number = match self.raw(key) {
+            Some(Raw::Override(value)) => value
+                .trim()
+                .parse::<i64>()
+                .map_err(|_| invalid(key, &value, "expected an integer"))?,
+            Some(Raw::File(Value::Integer(value))) => *value,
+            Some(Raw::File(value)) => return Err(invalid(key, value, "expected an integer")),
+            None => {
+                return default.ok_or_else(|| ConfigError::Missing {
+                    key: key.to_string(),
+                })
+            }
+        };
+        usize::try_from(number).map_err(|_| invalid(key, number, "expected a positive integer"))
+    }
+
+    fn bool(&self, key: &str, default: bool) -> Result<bool, ConfigError> {
+        match self.raw(key) {
+            Some(Raw::Override(value)) => value
+                .trim()
+                .parse::<bool>()
+                .map_err(|_| invalid(key, &value, "expected true or false")),
+            Some(Raw::File(Value::Boolean(value))) => Ok(*value),
+            Some(Raw::File(value)) => Err(invalid(key, value, "expected true or false")),
+            None => Ok(default),
+        }
+    }
+
+    fn value_codec(&self, key: &str) -> Result<ValueCodec, ConfigError> {
+        let name = self.string(key, Some("msgpack"))?;
+        ValueCodec::from_name(&name).ok_or_else(|| invalid(key, &name, "expected msgpack or cbor"))
+    }
+
+    fn strings(&self, key: &str, required: bool) -> Result<Vec<String>, ConfigError> {
+        match self.raw(key) {
+            Some(Raw::Override(value)) => Ok(value
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect()),
+            Some(Raw::File(Value::Array(values))) => values
+                .iter()
+                .map(|value| match value {
+                    Value::String(s) => Ok(s.clone()),
+                    other => Err(invalid(key, other, "expected an array of strings")),
+                })
+                .collect(),
+            Some(Raw::File(value)) => Err(invalid(key, value, "expected an array of strings")),
+            None if !required => Ok(Vec::new()),
+            None => Err(ConfigError::Missing {
+                key: key.to_string(),
+            }),
+        }
+    }
+}
+
+impl Config {
+    // Load configuration, `--config` and `--set` flags are consumed from args
+    // remaining args (program name first) are returned for the binary's own positional arguments
+    pub fn from_args<I>(args: I, needs: Needs) -> Result<(Config, Vec<String>), ConfigError>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut config_path = None;
+        let mut cli = HashMap::new();
+        let mut rest = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--config" {
+                config_path = Some(
+                    args.next()
+                        .ok_or_else(|| ConfigError::Usage("--config requires a path".into()))?,
+                );
+            } else if let Some(path) = arg.strip_prefix("--config=") {
+                config_path = Some(path.to_string());
+            } else if arg == "--set" {
+                let assignment = args
+                    .next()
+                    .ok_or_else(|| ConfigError::Usage("--set requires key=value".into()))?;
+                let (key, value) = parse_assignment(&assignment)?;
+                cli.insert(key, value);
+            } else if let Some(assignment) = arg.strip_prefix("--set=") {
+                let (key, value) = parse_assignment(assignment)?;
+                cli.insert(key, value);
+            } else {
+                rest.push(arg);
+            }
+        }
+
+        let path = config_path.or_else(|| env::var(CONFIG_PATH_ENV).ok());
+        let explicit = path.is_some();
+        let path = path.unwrap_or_else(|| DEFAULT_CONFIG_PATH.to_string());
+
+        Ok((Config::load(&path, explicit, cli, needs)?, rest))
+    }
+
+    // a missing default file is an empty one, env vars and --set can give every key (missing keys are reported by name)
+    // a file named by --config or ELAROCKS_CONFIG has to exist
+    fn load(
+        path: &str,
+        explicit: bool,
+        cli: HashMap<String, String>,
+        needs: Needs,
+    ) -> Result<Config, ConfigError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if !explicit && e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(source) => {
+                return Err(ConfigError::Read {
+                    path: path.to_string(),
+                    source,
+                })
+            }
+        };
+        let table: Table = text
+            .parse()
+            .map_err(|e: toml::de::Error| ConfigError::Syntax {
+                path: path.to_string(),
+                message: e.to_string(),
+            })?;
+
+        check_known_keys(&table)?;
+        for key in cli.keys() {
+            if !KNOWN_KEYS.contains(&key.as_str()) {
+                return Err(ConfigError::UnknownKey { key: key.clone() });
+            }
+        }
+
+        let sources = Sources { table, cli };
+        let fetch = needs == Needs::Fetch;
+        // no default when the binary needs the key, empty otherwise
+        let required = |needed: bool| if needed { None } else { Some("") };
+        let config = Config {
+            elasticsearch: ElasticsearchConfig {
+                url: sources.string("elasticsearch.url", required(fetch))?,
+                username: sources.string("elasticsearch.username", required(fetch))?,
+                password: sources.string("elasticsearch.password", required(fetch))?,
+                indices: sources.strings("elasticsearch.indices", fetch)?,
+                timestamp_start: sources
+                    .string("elasticsearch.timestamp_start", required(fetch))?,
+                timestamp_end: sources.optional_string("elasticsearch.timestamp_end")?,
+                size: sources.usize("elasticsearch.size", Some(100))?,
+                pit_keep_alive: sources.string("elasticsearch.pit_keep_alive", Some("1m"))?,
+                state_file: sources.string("elasticsearch.state_file", Some("fetch_state.json"))?,
+            },
+            csv: CsvFileConfig {
+                save_location: sources.string("csv.save_location", required(fetch))?,
+                csv_name: sources.string("csv.csv_name", Some("_logs.csv"))?,
+            },
+            rocksdb: RocksDbConfig {
+                db_location: sources.string("rocksdb.db_location", None)?,
+                csv_location: sources
+                    .string("rocksdb.csv_location", required(needs == Needs::Load))?,
+                value_codec: sources.value_codec("rocksdb.value_codec")?,
+                batch_size: sources.usize("rocksdb.batch_size", Some(10_000))?,
+            },
+            output: OutputConfig {
+                csv: sources.bool("output.csv", true)?,
+                rocksdb: sources.bool("output.rocksdb", false)?,
+            },
+            server: ServerConfig {
+                listen: sources.string("server.listen", Some("127.0.0.1:8080"))?,
+            },
+        };
+        config.validate(needs)?;
+        Ok(config)
+    }
+
+    fn validate(&self, needs: Needs) -> Result<(), ConfigError> {
+        if self.rocksdb.batch_size == 0 {
+            return Err(invalid("rocksdb.batch_size", 0, "expected at least 1"));
+        }
+        if let Err(e) = self.server.listen.parse::<SocketAddr>() {
+            return Err(invalid("server.listen", &self.server.listen, e));
+        }
+        if self.rocksdb.db_location.is_empty() {
+            return Err(invalid("rocksdb.db_location", "\"\"", "must not be empty"));
+        }
+        if needs == Needs::Load && self.rocksdb.csv_location.is_empty() {
+            return Err(invalid("rocksdb.csv_location", "\"\"", "must not be empty"));
+        }
+        if needs == Needs::Fetch {
+            self.validate_fetch()?;
+        }
+        Ok(())
+    }
+
+    // elasticsearch, csv and output, only read by `main`
+    fn validate_fetch(&self) -> Result<(), ConfigError> {
+        let es = &self.elasticsearch;
+        if !(es.url.starts_with("http://") || es.url.starts_with("https://")) {
+            return Err(invalid(
+                "elasticsearch.url",
+                &es.url,
+                "expected http:// or https:// url",
+            ));
+        }
+        if es.indices.is_empty() {
+            return Err(invalid(
+                "elasticsearch.indices",
+                "[]",
+                "at least one index is required",
+            ));
+        }
+        let start = DateTime::parse_from_rfc3339(&es.timestamp_start)
+            .map_err(|e| invalid("elasticsearch.timestamp_start", &es.timestamp_start, e))?;
+        if let Some(timestamp_end) = &es.timestamp_end {
+            let end = DateTime::parse_from_rfc3339(timestamp_end)
+                .map_err(|e| invalid("elasticsearch.timestamp_end", timestamp_end, e))?;
+            if start >= end {
+                return Err(invalid(
+                    "elasticsearch.timestamp_end",
+                    timestamp_end,
+                    "must be later than elasticsearch.timestamp_start",
+                ));
+            }
+        }
+        // search_after pages can not be larger than default max_result_window
+        if es.size == 0 || es.size > 10_000 {
+            return Err(invalid(
+                "elasticsearch.size",
+                es.size,
+                "expected between 1 and 10000",
+            ));
+        }
+        if !self.output.csv && !self.output.rocksdb {
+            return Err(invalid(
+                "output.csv",
+                false,
+                "output.csv or output.rocksdb must be true",
+            ));
+        }
+        for (key, value) in [
+            ("elasticsearch.state_file", &es.state_file),
+            ("csv.save_location", &self.csv.save_location),
+            ("csv.csv_name", &self.csv.csv_name),
+        ] {
+            if value.is_empty() {
+                return Err(invalid(key, "\"\"", "must not be empty"));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_known_keys(table: &Table) -> Result<(), ConfigError> {
+    for (section, value) in table {
+        let Some(section_table) = value.as_table() else {
+            return Err(ConfigError::UnknownKey {
+                key: section.clone(),
+            });
+        };
+        for name in section_table.keys() {
+            let key = format!("{}.{}", section, name);
+            if !KNOWN_KEYS.contains(&key.as_str()) {
+                return Err(ConfigError::UnknownKey { key });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_assignment(assignment: &str) -> Result<(String, String), ConfigError> {
+    match assignment.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(ConfigError::Usage(format!(
+            "--set expects key=value, got {}",
+            assignment
+        ))),
+    }
+}
+
+// elasticsearch.pit_keep_alive → ELAROCKS_ELASTICSEARCH_PIT_KEEP_ALIVE
+fn env_name(key: &str) -> String {
+    format!("{}{}", ENV_PREFIX, key.replace('.', "_").to_uppercase())
+}
+
+fn invalid(key: &str, value: impl fmt::Display, reason: impl fmt::Display) -> ConfigError {
+    ConfigError::Invalid {
+        key: key.to_string(),
+        value: value.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MISSING: &str = "/nonexistent/elarocks.toml";
+
+    #[test]
+    fn missing_default_file_is_empty() {
+        let cli = HashMap::from([("rocksdb.db_location".to_string(), "/tmp/db".to_string())]);
+        let config = Config::load(MISSING, false, cli, Needs::Read).unwrap();
+        assert_eq!(config.rocksdb.db_location, "/tmp/db");
+        // what is missing is named, not the file
+        let e = Config::load(MISSING, false, HashMap::new(), Needs::Fetch)
+            .err()
+            .unwrap();
+        assert!(matches!(e, ConfigError::Missing { .. }), "{}", e);
+    }
+
+    #[test]
+    fn missing_explicit_file_is_an_error() {
+        let cli = HashMap::from([("rocksdb.db_location".to_string(), "/tmp/db".to_string())]);
+        let e = Config::load(MISSING, true, cli, Needs::Read).err().unwrap();
+        assert!(matches!(e, ConfigError::Read { .. }), "{}", e);
+    }
+}
@@ -1,581 +1,172 @@
-use crate::structs::events::*;
+use crate::storage::keys::record_hash;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use std::{fs, io};
 
-// trait for json data parsing and write to csv files
-pub(crate) trait EventToCSV: Sized {
-    fn parse(data: &serde_json::Value) -> Vec<Self>;
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> std::io::Result<()>;
+// Sysmon keys Winlogbeat's sysmon module moves out of `winlog.event_data`, and the ECS field that has the value
+const MOVED_FIELDS: [(&str, &str); 8] = [
+    ("ProcessGuid", "process.entity_id"),
+    ("ProcessId", "process.pid"),
+    ("Image", "process.executable"),
+    ("CommandLine", "process.command_line"),
+    ("ParentProcessGuid", "process.parent.entity_id"),
+    ("ParentProcessId", "process.parent.pid"),
+    ("ParentImage", "process.parent.executable"),
+    ("ParentCommandLine", "process.parent.command_line"),
+];
+
+// Sysmon Hashes order
+const HASH_ALGORITHMS: [&str; 4] = ["sha1", "md5", "sha256", "imphash"];
+
+// field of the hit source, "a.b" is field b of the object in field a
+fn source_field<'a>(hit: &'a serde_json::Value, path: &str) -> &'a serde_json::Value {
+    path.split('.')
+        .fold(&hit["_source"], |value, name| &value[name])
 }
 
-impl EventToCSV for Event1 {
-    // extract data from event
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                // parsing message field and combine with key-value
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    let mut entry = Event1 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("Process Create".to_string()),
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        image: None,
-                        file_version: None,
-                        description: None,
-                        product: None,
-                        company: None,
-                        original_file_name: None,
-                        command_line: None,
-                        current_directory: None,
-                        user: None,
-                        logon_guid: None,
-                        logon_id: None,
-                        terminal_session_id: None,
-                        integrity_level: None,
-                        hashes: None,
-                        parent_process_guid: None,
-                        parent_process_id: None,
-                        parent_image: None,
-                        parent_command_line: None,
-                        parent_user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "FileVersion" => entry.file_version = Some(value.to_string()),
-                                "Description" => entry.description = Some(value.to_string()),
-                                "Product" => entry.product = Some(value.to_string()),
-                                "Company" => entry.company = Some(value.to_string()),
-                                "OriginalFileName" => {
-                                    entry.original_file_name = Some(value.to_string())
-                                }
-                                "CommandLine" => entry.command_line = Some(value.to_string()),
-                                "CurrentDirectory" => {
-                                    entry.current_directory = Some(value.to_string())
-                                }
-                                "User" => entry.user = Some(value.to_string()),
-                                "LogonGuid" => entry.logon_guid = Some(value.to_string()),
-                                "LogonId" => entry.logon_id = Some(value.to_string()),
-                                "TerminalSessionId" => {
-                                    entry.terminal_session_id = Some(value.to_string())
-                                }
-                                "IntegrityLevel" => entry.integrity_level = Some(value.to_string()),
-                                "Hashes" => entry.hashes = Some(value.to_string()),
-                                "ParentProcessGuid" => {
-                                    entry.parent_process_guid = Some(value.to_string())
-                                }
-                                "ParentProcessId" => {
-                                    entry.parent_process_id = Some(value.to_string())
-                                }
-                                "ParentImage" => entry.parent_image = Some(value.to_string()),
-                                "ParentCommandLine" => {
-                                    entry.parent_command_line = Some(value.to_string())
-                                }
-                                "ParentUser" => entry.parent_user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    // receive event's vec and save to files
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                // write csv files \t seperate
-                .delimiter(b'\t')
-                // Don't write headers when appending.
-                .has_headers(false)
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
+fn text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(text) => Some(text.clone()),
+        serde_json::Value::Number(number) => Some(number.to_string()),
+        _ => None,
     }
 }
 
-impl EventToCSV for Event2 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event2 raw message: {}", message);
-                    let mut entry = Event2 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("File creation time changed".to_string()),
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        image: None,
-                        target_filename: None,
-                        creation_utc_time: None,
-                        previous_creation_utc_time: None,
-                        user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "TargetFilename" => entry.target_filename = Some(value.to_string()),
-                                "CreationUtcTime" => {
-                                    entry.creation_utc_time = Some(value.to_string())
-                                }
-                                "PreviousCreationUtcTime" => {
-                                    entry.previous_creation_utc_time = Some(value.to_string())
-                                }
-                                "User" => entry.user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
+// Sysmon keys of a module-processed hit that are not in `event_data` any more
+fn moved_fields(hit: &serde_json::Value) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    // "2023-08-06T15:00:01.311Z" → "2023-08-06 15:00:01.311", the UtcTime of the event
+    if let Some(time) = source_field(hit, "@timestamp")
+        .as_str()
+        .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+    {
+        let utc_time = time.with_timezone(&Utc).format("%Y-%m-%d %H:%M:%S%.3f");
+        fields.push(("UtcTime".to_string(), utc_time.to_string()));
+    }
+    for (key, path) in MOVED_FIELDS {
+        if let Some(value) = text(source_field(hit, path)) {
+            fields.push((key.to_string(), value));
+        }
+    }
+    // "DOMAIN\name"
+    if let Some(name) = source_field(hit, "user.name").as_str() {
+        let user = match source_field(hit, "user.domain").as_str() {
+            Some(domain) => format!("{}\\{}", domain, name),
+            None => name.to_string(),
         };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
+        fields.push(("User".to_string(), user));
+    }
+    // the loaded / written file for image and file events, the process for process create
+    let hash = match source_field(hit, "file.hash") {
+        serde_json::Value::Object(_) => source_field(hit, "file.hash"),
+        _ => source_field(hit, "process.hash"),
+    };
+    let hashes: Vec<String> = HASH_ALGORITHMS
+        .iter()
+        .filter_map(|algorithm| {
+            let digest = hash[algorithm].as_str()?;
+            Some(format!("{}={}", algorithm.to_ascii_uppercase(), digest))
+        })
+        .collect();
+    if !hashes.is_empty() {
+        fields.push(("Hashes".to_string(), hashes.join(",")));
+    }
+    fields
 }
 
-impl EventToCSV for Event3 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event3 raw message: {}", message);
-                    let mut entry = Event3 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("Network connection detected".to_string()),
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        image: None,
-                        user: None,
-                        protocol: None,
-                        initiated: None,
-                        source_is_ipv6: None,
-                        source_ip: None,
-                        source_hostname: None,
-                        source_port: None,
-                        source_port_name: None,
-                        destination_is_ipv6: None,
-                        destination_ip: None,
-                        destination_hostname: None,
-                        destination_port: None,
-                        destination_port_name: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "User" => entry.user = Some(value.to_string()),
-                                "Protocol" => entry.protocol = Some(value.to_string()),
-                                "Initiated" => entry.initiated = Some(value.to_string()),
-                                "SourceIsIpv6" => entry.source_is_ipv6 = Some(value.to_string()),
-                                "SourceIp" => entry.source_ip = Some(value.to_string()),
-                                "SourceHostname" => entry.source_hostname = Some(value.to_string()),
-                                "SourcePort" => entry.source_port = Some(value.to_string()),
-                                "SourcePortName" => {
-                                    entry.source_port_name = Some(value.to_string())
-                                }
-                                "DestinationIsIpv6" => {
-                                    entry.destination_is_ipv6 = Some(value.to_string())
-                                }
-                                "DestinationIp" => entry.destination_ip = Some(value.to_string()),
-                                "DestinationHostname" => {
-                                    entry.destination_hostname = Some(value.to_string())
-                                }
-                                "DestinationPort" => {
-                                    entry.destination_port = Some(value.to_string())
-                                }
-                                "DestinationPortName" => {
-                                    entry.destination_port_name = Some(value.to_string())
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                // write csv files \t seperate
-                .delimiter(b'\t')
-                // no headers when file is exists
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
+// Sysmon key-value pairs of a hit
+// Winlogbeat ships them in `winlog.event_data` with exact values (multi-line command lines); its sysmon module
+// moves UtcTime, ProcessGuid, ProcessId, Image, CommandLine, Hashes, ... into ECS fields, read from there
+// hits without `event_data` only have the rendered `message`, split on newlines and the first ':'
+fn sysmon_fields(hit: &serde_json::Value) -> Option<Vec<(String, String)>> {
+    let Some(event_data) = hit["_source"]["winlog"]["event_data"].as_object() else {
+        let message = hit["_source"]["message"].as_str()?;
+        return Some(
+            message
+                .split('\n')
+                .filter_map(|part| part.split_once(':'))
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                .collect(),
+        );
+    };
+
+    let mut fields: Vec<(String, String)> = event_data
+        .iter()
+        .map(|(key, value)| {
+            (
+                key.clone(),
+                text(value).unwrap_or_else(|| value.to_string()),
+            )
+        })
+        .collect();
+    for (key, value) in moved_fields(hit) {
+        if !fields.iter().any(|(name, _)| *name == key) {
+            fields.push((key, value));
+        }
+    }
+    Some(fields)
 }
 
-impl EventToCSV for Event5 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event5 raw message: {}", message);
-                    let mut entry = Event5 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("Process terminated".to_string()),
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        image: None,
-                        user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "User" => entry.user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
-}
+// trait for json data parsing and write to csv files
+// structs and field mapping come from the `sysmon_event!` table in structs::events
+pub trait EventToCSV: Sized + Serialize {
+    // Sysmon event.code, type id of RocksDB keys
+    const EVENT_CODE: u8;
 
-impl EventToCSV for Event7 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
+    // "event.action" written to every row
+    const EVENT_ACTION: &'static str;
 
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event7 raw message: {}", message);
-                    let mut entry = Event7 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("Image loaded".to_string()),
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        image: None,
-                        image_loaded: None,
-                        file_version: None,
-                        description: None,
-                        product: None,
-                        company: None,
-                        original_file_name: None,
-                        hashes: None,
-                        signed: None,
-                        signature: None,
-                        signature_status: None,
-                        user: None,
-                    };
+    // CSV header, column names in the written order
+    const FIELDS: &'static [&'static str];
 
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
+    // empty entry, only agent fields, event_action and record_id filled
+    fn new(agent_name: Option<String>, agent_id: Option<String>, record_id: Option<String>)
+        -> Self;
 
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
+    // store one Sysmon key-value pair, keys not in the table are ignored
+    fn set_field(&mut self, key: &str, value: &str);
 
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "ImageLoaded" => entry.image_loaded = Some(value.to_string()),
-                                "FileVersion" => entry.file_version = Some(value.to_string()),
-                                "Description" => entry.description = Some(value.to_string()),
-                                "Product" => entry.product = Some(value.to_string()),
-                                "Company" => entry.company = Some(value.to_string()),
-                                "OriginalFileName" => {
-                                    entry.original_file_name = Some(value.to_string())
-                                }
-                                "Hashes" => entry.hashes = Some(value.to_string()),
-                                "Signed" => entry.signed = Some(value.to_string()),
-                                "Signature" => entry.signature = Some(value.to_string()),
-                                "SignatureStatus" => {
-                                    entry.signature_status = Some(value.to_string())
-                                }
-                                "User" => entry.user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
+    // value of a CSV column by name, None when empty or not a column
+    fn field(&self, name: &str) -> Option<&str>;
 
-                    entries.push(entry);
-                }
+    // stable per-record part of the RocksDB key, the same event always gets the same key
+    // ProcessGuid + RecordID (event log record number of the agent), or every column for CSV files without record_id
+    fn discriminator(&self) -> u64 {
+        match self.field("record_id") {
+            Some(record_id) => {
+                record_hash(&[self.field("process_guid").unwrap_or_default(), record_id])
             }
+            None => record_hash(
+                &Self::FIELDS
+                    .iter()
+                    .map(|name| self.field(name).unwrap_or_default())
+                    .collect::<Vec<&str>>(),
+            ),
         }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
     }
-}
 
-impl EventToCSV for Event9 {
+    // extract data from event
     fn parse(data: &serde_json::Value) -> Vec<Self> {
         let mut entries = Vec::new();
 
         if let Some(hits) = data["hits"]["hits"].as_array() {
             for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event9 raw message: {}", message);
-                    let mut entry = Event9 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("RawAccessRead detected".to_string()),
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        image: None,
-                        device: None,
-                        user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "Device" => entry.device = Some(value.to_string()),
-                                "User" => entry.user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
+                // parsing event_data (or message) fields and combine with key-value
+                if let Some(fields) = sysmon_fields(hit) {
+                    let agent = &hit["_source"]["agent"];
+                    // record_id is a number in winlogbeat documents
+                    let record_id = &hit["_source"]["winlog"]["record_id"];
+                    let mut entry = Self::new(
+                        agent["name"].as_str().map(str::to_string),
+                        agent["id"].as_str().map(str::to_string),
+                        record_id
+                            .as_u64()
+                            .map(|id| id.to_string())
+                            .or_else(|| record_id.as_str().map(str::to_string)),
+                    );
+
+                    for (key, value) in &fields {
+                        entry.set_field(key, value);
                     }
 
                     entries.push(entry);
@@ -586,6 +177,7 @@ impl EventToCSV for Event9 {
         entries
     }
 
+    // receive event's vec and save to files
     fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
         // Check if entries is empty, and if so, return early.
         if entries.is_empty() {
@@ -597,8 +189,10 @@ impl EventToCSV for Event9 {
         let mut wtr = if file_exists {
             // Open the file in append mode if it exists.
             csv::WriterBuilder::new()
+                // write csv files \t seperate
                 .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
+                // Don't write headers when appending.
+                .has_headers(false)
                 .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
         } else {
             // Create a new file if it doesn't exist.
@@ -615,1064 +209,116 @@ impl EventToCSV for Event9 {
     }
 }
 
-impl EventToCSV for Event10 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event10 raw message: {}", message);
-                    let mut entry = Event10 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("Process accessed".to_string()),
-                        utc_time: None,
-                        source_process_guid: None,
-                        source_process_id: None,
-                        source_thread_id: None,
-                        source_image: None,
-                        target_process_guid: None,
-                        target_process_id: None,
-                        target_thread_id: None,
-                        target_image: None,
-                        granted_access: None,
-                        call_trace: None,
-                        source_user: None,
-                        target_user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "SourceProcessGuid" => {
-                                    entry.source_process_guid = Some(value.to_string())
-                                }
-                                "SourceProcessId" => {
-                                    entry.source_process_id = Some(value.to_string())
-                                }
-                                "SourceThreadId" => {
-                                    entry.source_thread_id = Some(value.to_string())
-                                }
-                                "SourceImage" => entry.source_image = Some(value.to_string()),
-                                "TargetProcessGuid" => {
-                                    entry.target_process_guid = Some(value.to_string())
-                                }
-                                "TargetProcessId" => {
-                                    entry.target_process_id = Some(value.to_string())
-                                }
-                                "TargetThreadId" => {
-                                    entry.target_thread_id = Some(value.to_string())
-                                }
-                                "TargetImage" => entry.target_image = Some(value.to_string()),
-                                "GrantedAccess" => entry.granted_access = Some(value.to_string()),
-                                "CallTrace" => entry.call_trace = Some(value.to_string()),
-                                "SourceUser" => entry.source_user = Some(value.to_string()),
-                                "TargetUser" => entry.target_user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::events::Event1;
+    use serde_json::json;
+
+    // Process Create as stored by Winlogbeat 8.8 with its sysmon module: UtcTime, ProcessGuid, ProcessId, Image,
+    // CommandLine, User, Hashes, ... are ECS fields, `event_data` keeps the rest
+    fn module_hit() -> serde_json::Value {
+        json!({"hits": {"hits": [{
+            "_index": ".ds-winlogbeat-8.8.2-2023.08.06-000001",
+            "_id": "x3Wd0okBg3HUo0n2k1Gm",
+            "_source": {
+                "@timestamp": "2023-08-06T15:00:01.311Z",
+                "agent": {"name": "DESKTOP-1", "id": "7c8c1b8e-5a0f-4c9f-b3a1-4d1f0c6f9e20", "type": "winlogbeat"},
+                "event": {"code": "1", "module": "sysmon", "action": "Process Create"},
+                "user": {"name": "user", "domain": "DESKTOP-1"},
+                "process": {
+                    "entity_id": "{d9d3b9b4-b5b1-64cf-8e05-000000000e00}",
+                    "pid": 6348,
+                    "executable": "C:\\Windows\\System32\\cmd.exe",
+                    "command_line": "cmd.exe /c \"echo a\"",
+                    "hash": {"sha256": "b99d114b267ffd068c3289199b6df95a9f9e64872d6c2a666bc7fde7b2c1b6e1"}
+                },
+                "winlog": {
+                    "record_id": 18562,
+                    "event_data": {
+                        "RuleName": "-",
+                        "FileVersion": "10.0.19041.1",
+                        "Description": "Windows Command Processor",
+                        "IntegrityLevel": "High",
+                        "LogonId": "0x2a0d1"
+                    }
+                },
+                "message": "Process Create:\nRuleName: -\nUtcTime: 2023-08-06 15:00:01.311\nProcessGuid: {d9d3b9b4-b5b1-64cf-8e05-000000000e00}\nProcessId: 6348\nImage: C:\\Windows\\System32\\cmd.exe\nFileVersion: 10.0.19041.1 (WinBuild.160101.0800)\nDescription: Windows Command Processor\nCommandLine: cmd.exe /c \"echo a\"\nUser: DESKTOP-1\\user\nLogonId: 0x2A0D1\nIntegrityLevel: High\nHashes: SHA256=B99D114B267FFD068C3289199B6DF95A9F9E64872D6C2A666BC7FDE7B2C1B6E1\nParentProcessGuid: {d9d3b9b4-b5a0-64cf-7c05-000000000e00}"
             }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
-}
-
-impl EventToCSV for Event11 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event11 raw message: {}", message);
-                    let mut entry = Event11 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("File created".to_string()),
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        image: None,
-                        target_filename: None,
-                        creation_utc_time: None,
-                        user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "TargetFilename" => entry.target_filename = Some(value.to_string()),
-                                "CreationUtcTime" => {
-                                    entry.creation_utc_time = Some(value.to_string())
-                                }
-                                "User" => entry.user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
-}
-
-impl EventToCSV for Event12 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event12 raw message: {}", message);
-                    let mut entry = Event12 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("Registry object added or deleted".to_string()),
-                        utc_time: None,
-                        event_type: None,
-                        process_guid: None,
-                        process_id: None,
-                        image: None,
-                        target_object: None,
-                        user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "EventType" => entry.event_type = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "TargetObject" => entry.target_object = Some(value.to_string()),
-                                "User" => entry.user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
-}
-
-impl EventToCSV for Event13 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event13 raw message: {}", message);
-                    let mut entry = Event13 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("Registry value set".to_string()),
-                        event_type: None,
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        image: None,
-                        target_object: None,
-                        details: None,
-                        user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "EventType" => entry.event_type = Some(value.to_string()),
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "TargetObject" => entry.target_object = Some(value.to_string()),
-                                "Details" => entry.details = Some(value.to_string()),
-                                "User" => entry.user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
-}
-
-impl EventToCSV for Event14 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event14 raw message: {}", message);
-                    let mut entry = Event14 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("Registry value set".to_string()),
-                        event_type: None,
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        image: None,
-                        target_object: None,
-                        new_name: None,
-                        user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "EventType" => entry.event_type = Some(value.to_string()),
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "TargetObject" => entry.target_object = Some(value.to_string()),
-                                "NewName" => entry.new_name = Some(value.to_string()),
-                                "User" => entry.user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
-}
-
-impl EventToCSV for Event15 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event15 raw message: {}", message);
-                    let mut entry = Event15 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("File stream created".to_string()),
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        image: None,
-                        target_filename: None,
-                        creation_utc_time: None,
-                        hash: None,
-                        contents: None,
-                        user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "TargetFilename" => entry.target_filename = Some(value.to_string()),
-                                "CreationUtcTime" => {
-                                    entry.creation_utc_time = Some(value.to_string())
-                                }
-                                "Hash" => entry.hash = Some(value.to_string()),
-                                "Contents" => entry.contents = Some(value.to_string()),
-                                "User" => entry.user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
-}
-
-impl EventToCSV for Event17 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event17 raw message: {}", message);
-                    let mut entry = Event17 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("Pipe Created".to_string()),
-                        utc_time: None,
-                        event_type: None,
-                        process_guid: None,
-                        process_id: None,
-                        pipe_name: None,
-                        image: None,
-                        user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "EventType" => entry.event_type = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "PipeName" => entry.pipe_name = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "User" => entry.user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
-}
-
-impl EventToCSV for Event18 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event18 raw message: {}", message);
-                    let mut entry = Event18 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("Pipe Connected".to_string()),
-                        utc_time: None,
-                        event_type: None,
-                        process_guid: None,
-                        process_id: None,
-                        pipe_name: None,
-                        image: None,
-                        user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "EventType" => entry.event_type = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "PipeName" => entry.pipe_name = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "User" => entry.user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
-}
-
-impl EventToCSV for Event22 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event22 raw message: {}", message);
-                    let mut entry = Event22 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("Dns query".to_string()),
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        query_name: None,
-                        query_status: None,
-                        query_results: None,
-                        image: None,
-                        user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "QueryName" => entry.query_name = Some(value.to_string()),
-                                "QueryStatus" => entry.query_status = Some(value.to_string()),
-                                "QueryResults" => entry.query_results = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "User" => entry.user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
-}
-
-impl EventToCSV for Event23 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event23 raw message: {}", message);
-                    let mut entry = Event23 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("File Delete archived,".to_string()),
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        user: None,
-                        image: None,
-                        target_filename: None,
-                        hashes: None,
-                        is_executable: None,
-                        archived: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "User" => entry.user = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "TargetFilename" => entry.target_filename = Some(value.to_string()),
-                                "Hashes" => entry.hashes = Some(value.to_string()),
-                                "IsExecutable" => entry.is_executable = Some(value.to_string()),
-                                "Archived" => entry.archived = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
-}
-
-impl EventToCSV for Event25 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event25 raw message: {}", message);
-                    let mut entry = Event25 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("Process Tampering,".to_string()),
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        image: None,
-                        types: None,
-                        user: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "Type" => entry.types = Some(value.to_string()),
-                                "User" => entry.user = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
-    }
-}
-
-impl EventToCSV for Event26 {
-    fn parse(data: &serde_json::Value) -> Vec<Self> {
-        let mut entries = Vec::new();
-
-        if let Some(hits) = data["hits"]["hits"].as_array() {
-            for hit in hits {
-                if let Some(message) = hit["_source"]["message"].as_str() {
-                    // println!("Event26 raw message: {}", message);
-                    let mut entry = Event26 {
-                        agent_name: None,
-                        agent_id: None,
-                        event_action: Some("File Delete logged,".to_string()),
-                        utc_time: None,
-                        process_guid: None,
-                        process_id: None,
-                        user: None,
-                        image: None,
-                        target_filename: None,
-                        hashes: None,
-                        is_executable: None,
-                    };
-
-                    if let Some(agent_name) = hit["_source"]["agent"]["name"].as_str() {
-                        entry.agent_name = Some(agent_name.to_string());
-                    }
-
-                    if let Some(agent_id) = hit["_source"]["agent"]["id"].as_str() {
-                        entry.agent_id = Some(agent_id.to_string());
-                    }
-
-                    for part in message.split('\n') {
-                        let segments: Vec<_> = part.splitn(2, ':').collect();
-                        // println!("{:?}", segments); // Debug prints
-                        if segments.len() == 2 {
-                            let key = segments[0].trim();
-                            let value = segments[1].trim();
-                            match key {
-                                "UtcTime" => entry.utc_time = Some(value.to_string()),
-                                "ProcessGuid" => entry.process_guid = Some(value.to_string()),
-                                "ProcessId" => entry.process_id = Some(value.to_string()),
-                                "User" => entry.user = Some(value.to_string()),
-                                "Image" => entry.image = Some(value.to_string()),
-                                "TargetFilename" => entry.target_filename = Some(value.to_string()),
-                                "Hashes" => entry.hashes = Some(value.to_string()),
-                                "IsExecutable" => entry.is_executable = Some(value.to_string()),
-                                _ => {}
-                            }
-                        }
-                    }
-
-                    entries.push(entry);
-                }
-            }
-        }
-
-        entries
-    }
-
-    fn write_to_csv(entries: &Vec<Self>, filename: &str) -> io::Result<()> {
-        // Check if entries is empty, and if so, return early.
-        if entries.is_empty() {
-            return Ok(());
-        }
-
-        let file_exists = fs::metadata(filename).is_ok();
-
-        let mut wtr = if file_exists {
-            // Open the file in append mode if it exists.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .has_headers(false) // Don't write headers when appending.
-                .from_writer(fs::OpenOptions::new().append(true).open(filename)?)
-        } else {
-            // Create a new file if it doesn't exist.
-            csv::WriterBuilder::new()
-                .delimiter(b'\t')
-                .from_path(filename)?
-        };
-
-        for entry in entries {
-            wtr.serialize(entry)?;
-        }
-        wtr.flush()?;
-        Ok(())
+        }]}})
+    }
+
+    #[test]
+    fn module_processed_hit_keeps_moved_fields() {
+        let entries = Event1::parse(&module_hit());
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.field("utc_time"), Some("2023-08-06 15:00:01.311"));
+        assert_eq!(
+            entry.field("process_guid"),
+            Some("{d9d3b9b4-b5b1-64cf-8e05-000000000e00}")
+        );
+        assert_eq!(entry.field("process_id"), Some("6348"));
+        assert_eq!(entry.field("image"), Some("C:\\Windows\\System32\\cmd.exe"));
+        assert_eq!(entry.field("user"), Some("DESKTOP-1\\user"));
+        assert_eq!(entry.field("record_id"), Some("18562"));
+        assert_eq!(
+            entry.field("agent_id"),
+            Some("7c8c1b8e-5a0f-4c9f-b3a1-4d1f0c6f9e20")
+        );
+    }
+
+    #[test]
+    fn event_data_values_are_used_over_the_message() {
+        let entry = &Event1::parse(&module_hit())[0];
+        assert_eq!(entry.field("file_version"), Some("10.0.19041.1"));
+        assert_eq!(entry.field("logon_id"), Some("0x2a0d1"));
+        assert_eq!(
+            entry.field("hashes"),
+            Some("SHA256=b99d114b267ffd068c3289199b6df95a9f9e64872d6c2a666bc7fde7b2c1b6e1")
+        );
+        // only in the message
+        assert_eq!(entry.field("parent_process_guid"), None);
+    }
+
+    #[test]
+    fn multi_line_command_line_is_kept() {
+        let command_line = "powershell -c \"Write-Host a\nLogonId: 0x1\nWrite-Host b\"";
+        let mut hit = module_hit();
+        let source = &mut hit["hits"]["hits"][0]["_source"];
+        source["process"]["command_line"] = json!(command_line);
+        source["message"] = json!(format!(
+            "Process Create:\nCommandLine: {}\nLogonId: 0x2A0D1",
+            command_line
+        ));
+        let entry = &Event1::parse(&hit)[0];
+        assert_eq!(entry.field("command_line"), Some(command_line));
+        assert_eq!(entry.field("logon_id"), Some("0x2a0d1"));
+    }
+
+    #[test]
+    fn message_is_read_without_event_data() {
+        let mut hit = module_hit();
+        let source = &mut hit["hits"]["hits"][0]["_source"];
+        source["winlog"]
+            .as_object_mut()
+            .unwrap()
+            .remove("event_data");
+        let entry = &Event1::parse(&hit)[0];
+        assert_eq!(entry.field("utc_time"), Some("2023-08-06 15:00:01.311"));
+        assert_eq!(
+            entry.field("file_version"),
+            Some("10.0.19041.1 (WinBuild.160101.0800)")
+        );
+        assert_eq!(
+            entry.field("parent_process_guid"),
+            Some("{d9d3b9b4-b5a0-64cf-7c05-000000000e00}")
+        );
+    }
+
+    #[test]
+    fn hit_without_fields_is_skipped() {
+        let data = json!({"hits": {"hits": [{"_source": {"agent": {"id": "a"}}}]}});
+        assert!(Event1::parse(&data).is_empty());
     }
 }
@@ -1,7 +1,7 @@
 #![allow(non_snake_case)]
 
 pub mod byEventsEnv;
-pub mod db;
-pub mod elastic;
-pub mod env;
+pub mod cli;
+pub mod config;
 pub mod implements;
+pub mod state;
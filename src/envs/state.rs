@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::{fs, io};
+
+// Number of leading sort values that identify a hit across runs
+// "@timestamp", "agent.id", "winlog.record_id" (the trailing "_shard_doc" only means something inside one point-in-time)
+pub const MARK_SORT_FIELDS: usize = 3;
+
+// Last hit written to CSV for one index and event code
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HighWaterMark {
+    // "@timestamp" of the hit, next run searches from here (inclusive)
+    pub timestamp: String,
+    // first MARK_SORT_FIELDS sort values of the hit
+    pub sort: Vec<Value>,
+}
+
+impl HighWaterMark {
+    // Build mark from a hit of a sorted search response
+    pub fn from_hit(hit: &Value) -> Option<HighWaterMark> {
+        let timestamp = hit["_source"]["@timestamp"].as_str()?.to_string();
+        let sort = hit["sort"].as_array()?;
+        if sort.len() < MARK_SORT_FIELDS {
+            return None;
+        }
+        Some(HighWaterMark {
+            timestamp,
+            sort: sort[..MARK_SORT_FIELDS].to_vec(),
+        })
+    }
+
+    // true when the hit was already written by a previous run (sorts at or before the mark)
+    pub fn covers(&self, hit: &Value) -> bool {
+        match hit["sort"].as_array() {
+            Some(sort) => compare_sort_values(sort, &self.sort) != Ordering::Greater,
+            None => false,
+        }
+    }
+}
+
+// numbers of any JSON representation: unsigned above i64::MAX (unsigned_long), negative, then floats
+fn compare_numbers(left: &Number, right: &Number) -> Ordering {
+    if let (Some(l), Some(r)) = (left.as_u64(), right.as_u64()) {
+        return l.cmp(&r);
+    }
+    if let (Some(l), Some(r)) = (left.as_i64(), right.as_i64()) {
+        return l.cmp(&r);
+    }
+    // one side negative and the other above i64::MAX, or a float
+    match (left.as_f64(), right.as_f64()) {
+        (Some(l), Some(r)) => l.total_cmp(&r),
+        _ => Ordering::Equal,
+    }
+}
+
+// Compare sort values the way elasticsearch ordered them
+// numbers (epoch millis, record ids) numerically, keywords byte-wise, missing values last
+fn compare_sort_values(left: &[Value], right: &[Value]) -> Ordering {
+    for (l, r) in left.iter().zip(right) {
+        let ordering = match (l, r) {
+            (Value::Number(l), Value::Number(r)) => compare_numbers(l, r),
+            (Value::String(l), Value::String(r)) => l.as_bytes().cmp(r.as_bytes()),
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Null, _) => Ordering::Greater,
+            (_, Value::Null) => Ordering::Less,
+            _ => Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+// High-water marks of every index and event code, persisted as JSON
+// { "<index>": { "<event code>": { "timestamp": "...", "sort": [...] } } }
+pub struct FetchState {
+    path: String,
+    marks: BTreeMap<String, BTreeMap<String, HighWaterMark>>,
+}
+
+impl FetchState {
+    // Read state file, missing file means nothing was fetched yet
+    pub fn load(path: &str) -> io::Result<FetchState> {
+        let marks = match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(FetchState {
+            path: path.to_string(),
+            marks,
+        })
+    }
+
+    pub fn get(&self, index: &str, event_code: &str) -> Option<&HighWaterMark> {
+        self.marks.get(index)?.get(event_code)
+    }
+
+    // Move the mark forward and save, called only after the page is written to CSV
+    pub fn advance(
+        &mut self,
+        index: &str,
+        event_code: &str,
+        mark: HighWaterMark,
+    ) -> io::Result<()> {
+        self.marks
+            .entry(index.to_string())
+            .or_default()
+            .insert(event_code.to_string(), mark);
+        self.save()
+    }
+
+    // write to temporary file then rename, an interrupted run never leaves a half written state
+    fn save(&self) -> io::Result<()> {
+        let temp_path = format!("{}.tmp", self.path);
+        let text = serde_json::to_string_pretty(&self.marks)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(&temp_path, text)?;
+        fs::rename(&temp_path, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sort(values: Value) -> Vec<Value> {
+        values.as_array().unwrap().clone()
+    }
+
+    #[test]
+    fn sort_values_like_elasticsearch() {
+        let mark = sort(json!([1_691_334_001_311_i64, "agent-b", 100]));
+        let cases = [
+            (
+                json!([1_691_334_001_310_i64, "agent-z", 999]),
+                Ordering::Less,
+            ),
+            (
+                json!([1_691_334_001_311_i64, "agent-a", 999]),
+                Ordering::Less,
+            ),
+            (
+                json!([1_691_334_001_311_i64, "agent-b", 99]),
+                Ordering::Less,
+            ),
+            (
+                json!([1_691_334_001_311_i64, "agent-b", 100]),
+                Ordering::Equal,
+            ),
+            (
+                json!([1_691_334_001_311_i64, "agent-b", 101]),
+                Ordering::Greater,
+            ),
+            (
+                json!([1_691_334_001_311_i64, "agent-c", 0]),
+                Ordering::Greater,
+            ),
+            // missing values sort last
+            (json!([1_691_334_001_311_i64, null, 0]), Ordering::Greater),
+        ];
+        for (hit, ordering) in cases {
+            assert_eq!(
+                compare_sort_values(&sort(hit.clone()), &mark),
+                ordering,
+                "{}",
+                hit
+            );
+        }
+        // above i64::MAX, negative and floats are not equal to everything
+        let big = sort(json!([u64::MAX - 1]));
+        assert_eq!(
+            compare_sort_values(&sort(json!([u64::MAX])), &big),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_sort_values(&sort(json!([u64::MAX - 2])), &big),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_sort_values(&sort(json!([-1])), &big),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_sort_values(&sort(json!([1.5])), &sort(json!([1]))),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_sort_values(&sort(json!([1.5])), &sort(json!([2]))),
+            Ordering::Less
+        );
+        // keywords byte-wise, not by locale
+        assert_eq!(
+            compare_sort_values(&sort(json!(["Z"])), &sort(json!(["a"]))),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn marks_cover_hits_up_to_them() {
+        let hit = |sort: Value| json!({"_source": {"@timestamp": "2023-08-06T15:00:01.311Z"}, "sort": sort});
+        let mark =
+            HighWaterMark::from_hit(&hit(json!([1_691_334_001_311_i64, "agent", 7, 12]))).unwrap();
+        assert_eq!(mark.sort.len(), MARK_SORT_FIELDS);
+        assert!(mark.covers(&hit(json!([1_691_334_001_311_i64, "agent", 7, 99]))));
+        assert!(!mark.covers(&hit(json!([1_691_334_001_311_i64, "agent", 8, 0]))));
+        assert!(HighWaterMark::from_hit(&hit(json!([1]))).is_none());
+    }
+}
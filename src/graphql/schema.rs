@@ -0,0 +1,239 @@
+// GraphQL schema of `server` (POST /graphql), graphql/src/schema/typeDefs.js for every stored event type
+//
+// <Type>Eve(filter: SysmonFilter!, pagination: PaginationInput): <Type>EveConnection
+// nodes are the eventTypes structs (snake_case fields), cursors and pages those of storage/pagination.rs
+use crate::storage::pagination::{encode_cursor, Page, Pagination};
+use crate::storage::query::{FilterArgs, Query};
+use crate::structs::eventTypes::*;
+use async_graphql::connection::{Connection, Edge};
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, InputObject, Object, OutputType, Schema,
+    SimpleObject,
+};
+use chrono::DateTime;
+use rocksdb::DB;
+use serde::de::DeserializeOwned;
+use std::error::Error;
+use std::sync::Arc;
+
+pub type EventSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+// `db` is a secondary instance (`open_secondary`), every query first catches up with the writers
+pub fn schema(db: Arc<DB>) -> EventSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(db)
+        .finish()
+}
+
+// RFC 3339 ("2023-07-01T00:00:00.000Z"), both ends included
+#[derive(InputObject)]
+pub struct DateTimeRange {
+    pub start: String,
+    pub end: String,
+}
+
+// same filters as `FilterArgs`, a missing field is no filter
+#[derive(InputObject)]
+#[graphql(rename_fields = "snake_case")]
+pub struct SysmonFilter {
+    pub datetime: DateTimeRange,
+    pub process_id: Option<u32>,
+    pub process_guid: Option<String>,
+    pub parent_process_guid: Option<String>,
+    pub user: Option<String>,
+    pub agent_id: Option<String>,
+    pub image: Option<String>,
+    // ip or CIDR block ("10.0.0.0/8", "fe80::/10")
+    pub destination_ip: Option<String>,
+    // port or range ("443", "1024-2048")
+    pub destination_port: Option<String>,
+    pub destination_hostname: Option<String>,
+    pub query_name: Option<String>,
+    // process create / image loaded, "SHA256=...", "IMPHASH=..."
+    pub hash: Option<String>,
+}
+
+// first / after or last / before (Relay), 10 events when no size is given
+// offset skips that many events from the start of the search (the cursor is then not used)
+#[derive(InputObject, Default)]
+pub struct PaginationInput {
+    pub first: Option<usize>,
+    pub last: Option<usize>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub offset: Option<usize>,
+}
+
+#[derive(SimpleObject)]
+pub struct TotalCount {
+    // matching events of the whole time range, only counted when selected
+    total_count: Option<usize>,
+}
+
+type EventConnection<T> = Connection<String, T, TotalCount>;
+
+fn query_error(e: Box<dyn Error>) -> async_graphql::Error {
+    async_graphql::Error::new(e.to_string())
+}
+
+fn nanos(datetime: &str) -> Result<i64, Box<dyn Error>> {
+    DateTime::parse_from_rfc3339(datetime)
+        .map_err(|e| format!("invalid datetime {}: {}", datetime, e))?
+        .timestamp_nanos_opt()
+        .ok_or_else(|| format!("datetime out of range: {}", datetime).into())
+}
+
+impl SysmonFilter {
+    fn args(&self) -> FilterArgs {
+        FilterArgs {
+            image: self.image.clone(),
+            process_id: self.process_id.map(|pid| pid.to_string()),
+            process_guid: self.process_guid.clone(),
+            parent_process_guid: self.parent_process_guid.clone(),
+            agent_id: self.agent_id.clone(),
+            user: self.user.clone(),
+            destination_ip: self.destination_ip.clone(),
+            destination_port: self.destination_port.clone(),
+            destination_hostname: self.destination_hostname.clone(),
+            query_name: self.query_name.clone(),
+            hash: self.hash.clone(),
+        }
+    }
+}
+
+// the page after the offset, the window count when `totalCount` is selected
+// nodes are decoded straight into the event struct
+fn run_page<T: DeserializeOwned>(
+    db: &DB,
+    query: &Query,
+    pagination: Pagination,
+    offset: Option<usize>,
+    total_count: bool,
+) -> Result<(Page<T>, Option<usize>), Box<dyn Error>> {
+    db.try_catch_up_with_primary()?;
+    let pagination = pagination.skip(db, query, offset.unwrap_or(0))?;
+    let page = query.page_as::<T>(db, &pagination)?;
+    let total_count = if total_count {
+        Some(query.count(db)?)
+    } else {
+        None
+    };
+    Ok((page, total_count))
+}
+
+async fn events<T: OutputType + DeserializeOwned + Send + 'static>(
+    ctx: &Context<'_>,
+    event_action: &str,
+    filter: SysmonFilter,
+    pagination: Option<PaginationInput>,
+) -> async_graphql::Result<EventConnection<T>> {
+    let db = ctx.data::<Arc<DB>>()?.clone();
+    let pagination = pagination.unwrap_or_default();
+    let offset = pagination.offset;
+    let paging = Pagination::new(
+        pagination.first,
+        pagination.after.as_deref(),
+        pagination.last,
+        pagination.before.as_deref(),
+        10,
+    )
+    .map_err(query_error)?;
+    // counting reads the whole window
+    let total_count = ctx.look_ahead().field("totalCount").exists();
+    let query = Query::new(
+        event_action,
+        nanos(&filter.datetime.start).map_err(query_error)?,
+        nanos(&filter.datetime.end).map_err(query_error)?,
+    )
+    .map_err(query_error)?
+    .filter(filter.args().filters().map_err(query_error)?);
+
+    // RocksDB reads block, they run off the async workers
+    let (page, total_count) = tokio::task::spawn_blocking(move || {
+        run_page::<T>(&db, &query, paging, offset, total_count).map_err(|e| e.to_string())
+    })
+    .await??;
+
+    let mut connection = Connection::with_additional_fields(
+        page.has_previous_page,
+        page.has_next_page,
+        TotalCount { total_count },
+    );
+    for (key, node) in page.events {
+        connection.edges.push(Edge::new(encode_cursor(&key), node));
+    }
+    Ok(connection)
+}
+
+// one query field per stored event type, named after its node type (`reg_value_set_eve` → RegValueSetEve)
+macro_rules! event_queries {
+    ($($field:ident: $event:ty => $action:literal,)*) => {
+        pub struct QueryRoot;
+
+        #[Object(name = "Query", rename_fields = "PascalCase")]
+        impl QueryRoot {
+            $(
+                async fn $field(
+                    &self,
+                    ctx: &Context<'_>,
+                    filter: SysmonFilter,
+                    pagination: Option<PaginationInput>,
+                ) -> async_graphql::Result<EventConnection<$event>> {
+                    events(ctx, $action, filter, pagination).await
+                }
+            )*
+        }
+    };
+}
+
+event_queries! {
+    process_create_eve: ProcessCreateEvent => "Process Create",
+    file_create_time_changed_eve: FileCreateTimeChangedEvent => "File creation time changed",
+    network_connection_eve: NetworkConnectionEvent => "Network connection detected",
+    process_terminated_eve: ProcessTerminatedEvent => "Process terminated",
+    driver_loaded_eve: DriverLoadedEvent => "Driver loaded",
+    image_loaded_eve: ImageLoadedEvent => "Image loaded",
+    create_remote_thread_eve: CreateRemoteThreadEvent => "CreateRemoteThread detected",
+    raw_access_read_eve: RawAccessReadEvent => "RawAccessRead detected",
+    process_accessed_eve: ProcessAccessedEvent => "Process accessed",
+    file_created_eve: FileCreatedEvent => "File created",
+    registry_object_added_or_deleted_eve: RegistryObjectAddedOrDeletedEvent => "Registry object added or deleted",
+    reg_value_set_eve: RegistryValueSetEvent => "Registry value set",
+    registry_object_renamed_eve: RegistryObjectRenamedEvent => "Registry object renamed",
+    file_stream_created_eve: FileStreamCreatedEvent => "File stream created",
+    sysmon_config_state_changed_eve: SysmonConfigStateChangedEvent => "Sysmon config state changed",
+    pipe_created_eve: PipeCreatedEvent => "Pipe Created",
+    pipe_connected_eve: PipeConnectedEvent => "Pipe Connected",
+    wmi_event_filter_eve: WmiEventFilterEvent => "WmiEventFilter activity detected",
+    wmi_event_consumer_eve: WmiEventConsumerEvent => "WmiEventConsumer activity detected",
+    wmi_event_consumer_to_filter_eve: WmiEventConsumerToFilterEvent => "WmiEventConsumerToFilter activity detected",
+    dns_query_eve: DnsQueryEvent => "Dns query",
+    file_delete_archived_eve: FileDeleteArchivedEvent => "File Delete archived",
+    clipboard_changed_eve: ClipboardChangedEvent => "Clipboard changed",
+    process_tampering_eve: ProcessTamperingEvent => "Process Tampering",
+    file_delete_logged_eve: FileDeleteLoggedEvent => "File Delete logged",
+    file_block_executable_eve: FileBlockExecutableEvent => "File Block Executable",
+    file_block_shredding_eve: FileBlockShreddingEvent => "File Block Shredding",
+    file_executable_detected_eve: FileExecutableDetectedEvent => "File Executable Detected",
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // event fields have the types of graphql/src/schema/typeDefs.js
+    #[test]
+    fn times_are_strings() {
+        let sdl = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .finish()
+            .sdl();
+        assert!(!sdl.contains("DateTime!"), "{}", sdl);
+        let process_create = sdl
+            .split("type ProcessCreateEve {")
+            .nth(1)
+            .and_then(|rest| rest.split('}').next())
+            .unwrap();
+        assert!(process_create.contains("utc_time: String!"));
+        assert!(sdl.contains("previous_creation_utc_time: String!"));
+    }
+}
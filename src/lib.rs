@@ -0,0 +1,21 @@
+// elarocks: Sysmon events from Elasticsearch (or the CSV files of `main`) into RocksDB, and the queries over them
+// the binaries (`main`, `rocks`, `select`, `nano-select`, `migrate`, `server`) are thin wrappers around this crate
+//
+// envs    : elarocks.toml settings, fetch state, raw event parsing
+// structs : raw (CSV) and typed (stored) Sysmon events, conversions
+// storage : keys, value codecs, column families, indexes, `Store` (writers) and `Query` (readers)
+// graphql : GraphQL schema of `server` over `Query`
+pub mod envs;
+pub mod graphql;
+pub mod storage;
+pub mod structs;
+
+pub use storage::families::open_families;
+pub use storage::index::Filters;
+pub use storage::keys::EventKey;
+pub use storage::pagination::{Page, Pagination};
+pub use storage::query::{FilterArgs, Query};
+pub use storage::store::Store;
+pub use storage::values::ValueCodec;
+pub use structs::eventTypes::*;
+pub use structs::events::EVENT_TYPES;
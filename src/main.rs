@@ -3,20 +3,26 @@ use base64::{engine::general_purpose, Engine as _};
 use rayon::prelude::*;
 use reqwest::header;
 use serde_json::json;
+use std::error::Error;
+use std::io;
+use std::sync::{Mutex, MutexGuard};
 use tokio;
 
-// Import Enviroments with secrect key (settings)
-// Import Sysmon event structs
-mod envs;
-mod structs;
+// runtime settings (elarocks.toml), Sysmon event structs and the RocksDB writer of the elarocks crate
+use elarocks::envs::config::{Config, ElasticsearchConfig, Needs};
+use elarocks::envs::state::{FetchState, HighWaterMark};
+use elarocks::storage::store::PageCounts;
+use elarocks::structs::eventTypes::*;
+use elarocks::structs::events::*;
+use elarocks::Store;
 
-// use Imports
-use envs::env::*;
-use structs::events::*;
+// writers of one search response page
+type CsvSink = fn(&serde_json::Value, &str, usize) -> io::Result<()>;
+type RocksDbSink = fn(&Store, &serde_json::Value) -> Result<PageCounts, Box<dyn Error>>;
 
 // Elasticearch client connection with bypass SSL (works with https)
-fn build_client() -> Result<reqwest::Client, reqwest::Error> {
-    let auth_value = format!("{}:{}", ID, PW);
+fn build_client(es: &ElasticsearchConfig) -> Result<reqwest::Client, reqwest::Error> {
+    let auth_value = format!("{}:{}", es.username, es.password);
     let auth_value_bytes = auth_value.as_bytes();
     let encoded: String = general_purpose::STANDARD_NO_PAD.encode(auth_value_bytes);
     let basic_auth_header = format!("Basic {}", encoded);
@@ -36,15 +42,23 @@ fn build_client() -> Result<reqwest::Client, reqwest::Error> {
 }
 
 // Modify query
-fn build_query(event_code: &str) -> serde_json::Value {
+// every page is read through the same point-in-time, `search_after` holds the sort values of the previous page's last hit
+// `resume_from` is "@timestamp" of the high-water mark left by the previous run (inclusive, already written hits are skipped later)
+fn build_query(
+    es: &ElasticsearchConfig,
+    event_code: &str,
+    resume_from: Option<&str>,
+    pit_id: &str,
+    search_after: Option<&serde_json::Value>,
+) -> serde_json::Value {
     // raw elasticsearch query
-    json!({
+    let mut query = json!({
         "query": {
             "bool": {
                 "must": [
                     { "term": {"event.code": event_code} },
                     { "term": {"event.module": "sysmon"} },
-                    { "range": {"@timestamp": {"gt": TIMESTAMP_START, "lt": TIMESTAMP_END}} },
+                    { "range": {"@timestamp": {"gt": es.timestamp_start, "lt": es.timestamp_end.as_deref().unwrap_or("now")}} },
                     // 1. Used instead of wildcard when message's type is "match_only_text"
                     // { "query_string": {
                     //     "fields": ["message"],
@@ -91,18 +105,77 @@ fn build_query(event_code: &str) -> serde_json::Value {
         //         "minimum_should_match": 1
             }
         },
-        "size": SIZE
-    })
+        "size": es.size,
+        "pit": { "id": pit_id, "keep_alive": es.pit_keep_alive },
+        // "_shard_doc" is the tiebreaker so hits sharing the same "@timestamp" are neither skipped nor repeated
+        // "agent.id", "winlog.record_id" keep the order stable across runs, they are saved in the high-water mark
+        "sort": [
+            { "@timestamp": "asc" },
+            { "agent.id": { "order": "asc", "unmapped_type": "keyword" } },
+            { "winlog.record_id": { "order": "asc", "unmapped_type": "keyword" } },
+            { "_shard_doc": "asc" }
+        ]
+    });
+
+    if let Some(timestamp) = resume_from {
+        if let Some(must) = query["query"]["bool"]["must"].as_array_mut() {
+            must.push(json!({ "range": {"@timestamp": {"gte": timestamp}} }));
+        }
+    }
+
+    if let Some(sort_values) = search_after {
+        query["search_after"] = sort_values.clone();
+    }
+
+    query
+}
+
+// Open point-in-time on index, searches through it see a consistent snapshot while paging
+async fn open_point_in_time(
+    es: &ElasticsearchConfig,
+    client: &reqwest::Client,
+    index: &str,
+) -> Result<String, Box<dyn Error>> {
+    let response: serde_json::Value = client
+        .post(format!(
+            "{}/{}/_pit?keep_alive={}",
+            es.url, index, es.pit_keep_alive
+        ))
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    match response["id"].as_str() {
+        Some(pit_id) => Ok(pit_id.to_string()),
+        None => Err(format!("Failed to open point-in-time on {}: {}", index, response).into()),
+    }
+}
+
+// Release point-in-time, otherwise it is held by elasticsearch until keep_alive expires
+async fn close_point_in_time(
+    es: &ElasticsearchConfig,
+    client: &reqwest::Client,
+    pit_id: &str,
+) -> Result<(), reqwest::Error> {
+    client
+        .delete(format!("{}/_pit", es.url))
+        .json(&json!({ "id": pit_id }))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
 }
 
 // Send query with "_search" option
+// index is not part of the url, it is bound to point-in-time id inside the query
 async fn send_request(
+    es: &ElasticsearchConfig,
     client: &reqwest::Client,
     query: &serde_json::Value,
-    index: &str,
 ) -> Result<serde_json::Value, reqwest::Error> {
     client
-        .post(&format!("{}/{}/_search", ES_URL, index))
+        .post(format!("{}/_search", es.url))
         .json(query)
         .send()
         .await?
@@ -111,62 +184,289 @@ async fn send_request(
 }
 
 // Query multiple Index with event_code
-async fn fetch_data_from_es(event_code: &str) -> Result<Vec<serde_json::Value>, reqwest::Error> {
-    let client = build_client()?;
-    let query = build_query(event_code);
-    let mut all_results = Vec::new();
+// each page is handed to `on_page` as soon as it arrives, so memory stays bound to one page
+// search resumes from the high-water mark of index and event code, which moves forward after every written page
+async fn fetch_data_from_es<F>(
+    es: &ElasticsearchConfig,
+    state: &Mutex<FetchState>,
+    event_code: &str,
+    mut on_page: F,
+) -> Result<(), Box<dyn Error>>
+where
+    F: FnMut(&serde_json::Value) -> Result<(), Box<dyn Error>>,
+{
+    let client = build_client(es)?;
     println!("\n");
-    for index in INDICES.iter() {
-        all_results.push(send_request(&client, &query, index).await?);
-        println!("Index {}", index)
+    for index in es.indices.iter() {
+        let previous_mark = lock_state(state).get(index, event_code).cloned();
+        let mut pit_id = open_point_in_time(es, &client, index).await?;
+        let mut search_after: Option<serde_json::Value> = None;
+        let mut pages = 0;
+
+        let result: Result<(), Box<dyn Error>> = loop {
+            let query = build_query(
+                es,
+                event_code,
+                previous_mark.as_ref().map(|mark| mark.timestamp.as_str()),
+                &pit_id,
+                search_after.as_ref(),
+            );
+            let data = match send_request(es, &client, &query).await {
+                Ok(data) => data,
+                Err(e) => break Err(e.into()),
+            };
+            if !data["error"].is_null() {
+                break Err(format!("Search failed on {}: {}", index, data["error"]).into());
+            }
+
+            let hits = match data["hits"]["hits"].as_array() {
+                Some(hits) if !hits.is_empty() => hits,
+                _ => break Ok(()),
+            };
+            let mark = match hits.last().and_then(HighWaterMark::from_hit) {
+                Some(mark) => mark,
+                None => {
+                    break Err(format!("Hit without @timestamp or sort values on {}", index).into())
+                }
+            };
+
+            // hits at the previous mark's "@timestamp" may already be in CSV, they always lead the page and are dropped
+            match &previous_mark {
+                Some(previous) if hits.first().is_some_and(|hit| previous.covers(hit)) => {
+                    let mut page = data.clone();
+                    if let Some(page_hits) = page["hits"]["hits"].as_array_mut() {
+                        page_hits.retain(|hit| !previous.covers(hit));
+                    }
+                    if let Err(e) = on_page(&page) {
+                        break Err(e);
+                    }
+                }
+                _ => {
+                    if let Err(e) = on_page(&data) {
+                        break Err(e);
+                    }
+                }
+            }
+            if let Err(e) = lock_state(state).advance(index, event_code, mark) {
+                break Err(format!("Failed to save {}: {}", es.state_file, e).into());
+            }
+            pages += 1;
+
+            // elasticsearch may renew point-in-time id on every response
+            if let Some(new_pit_id) = data["pit_id"].as_str() {
+                pit_id = new_pit_id.to_string();
+            }
+            // last page is shorter than size, no need to ask for an empty one
+            if hits.len() < es.size {
+                break Ok(());
+            }
+            search_after = hits.last().map(|hit| hit["sort"].clone());
+        };
+
+        if let Err(e) = close_point_in_time(es, &client, &pit_id).await {
+            eprintln!("Error closing point-in-time on {}: {:?}", index, e);
+        }
+        result?;
+        println!("Index {} ({} pages)", index, pages)
     }
+    Ok(())
+}
 
-    Ok(all_results)
+// poisoned lock only means another event code panicked, marks themselves are still consistent
+fn lock_state(state: &Mutex<FetchState>) -> MutexGuard<'_, FetchState> {
+    state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
 }
 
 // async main excute
 #[tokio::main]
 async fn main() {
+    // settings are read from elarocks.toml, see `envs::config` for overrides
+    let config = match Config::from_args(std::env::args(), Needs::Fetch) {
+        Ok((config, _)) => config,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let state = match FetchState::load(&config.elasticsearch.state_file) {
+        Ok(state) => Mutex::new(state),
+        Err(err) => {
+            eprintln!("Error reading {}: {}", config.elasticsearch.state_file, err);
+            std::process::exit(1);
+        }
+    };
+
+    // one RocksDB shared by every event code, each event type has its own column family and binary keys (storage/keys.rs)
+    let db = if config.output.rocksdb {
+        match Store::open(&config.rocksdb.db_location, config.rocksdb.value_codec) {
+            Ok(db) => Some(db),
+            Err(err) => {
+                eprintln!("Error opening {}: {}", config.rocksdb.db_location, err);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+
+    // Event4 : Sysmon service state changed 이벤트로 추출 불필요
     let event_codes = [
-        "1", "2", "3", "5", "7", "9", "10", "11", "12", "13", "14", "15", "17", "18", "22", "23",
-        "25", "26",
+        "1", "2", "3", "5", "6", "7", "8", "9", "10", "11", "12", "13", "14", "15", "16", "17",
+        "18", "19", "20", "21", "22", "23", "24", "25", "26", "27", "28", "29",
     ];
-
     // rayon `par_iter` to process each event code in parallel.
     event_codes.par_iter().for_each(|&event_code| {
-        // call `fetch_data_from_es` per event code and process then save files
+        // call 'process_event_data' for CSV and 'Store::store_page' for RocksDB output of each format
+        let (process_page, store_page): (CsvSink, RocksDbSink) = match event_code {
+            "1" => (
+                process_event_data::<Event1>,
+                Store::store_page::<Event1, ProcessCreateEvent>,
+            ),
+            "2" => (
+                process_event_data::<Event2>,
+                Store::store_page::<Event2, FileCreateTimeChangedEvent>,
+            ),
+            "3" => (
+                process_event_data::<Event3>,
+                Store::store_page::<Event3, NetworkConnectionEvent>,
+            ),
+            "5" => (
+                process_event_data::<Event5>,
+                Store::store_page::<Event5, ProcessTerminatedEvent>,
+            ),
+            "6" => (
+                process_event_data::<Event6>,
+                Store::store_page::<Event6, DriverLoadedEvent>,
+            ),
+            "7" => (
+                process_event_data::<Event7>,
+                Store::store_page::<Event7, ImageLoadedEvent>,
+            ),
+            "8" => (
+                process_event_data::<Event8>,
+                Store::store_page::<Event8, CreateRemoteThreadEvent>,
+            ),
+            "9" => (
+                process_event_data::<Event9>,
+                Store::store_page::<Event9, RawAccessReadEvent>,
+            ),
+            "10" => (
+                process_event_data::<Event10>,
+                Store::store_page::<Event10, ProcessAccessedEvent>,
+            ),
+            "11" => (
+                process_event_data::<Event11>,
+                Store::store_page::<Event11, FileCreatedEvent>,
+            ),
+            "12" => (
+                process_event_data::<Event12>,
+                Store::store_page::<Event12, RegistryObjectAddedOrDeletedEvent>,
+            ),
+            "13" => (
+                process_event_data::<Event13>,
+                Store::store_page::<Event13, RegistryValueSetEvent>,
+            ),
+            "14" => (
+                process_event_data::<Event14>,
+                Store::store_page::<Event14, RegistryObjectRenamedEvent>,
+            ),
+            "15" => (
+                process_event_data::<Event15>,
+                Store::store_page::<Event15, FileStreamCreatedEvent>,
+            ),
+            "16" => (
+                process_event_data::<Event16>,
+                Store::store_page::<Event16, SysmonConfigStateChangedEvent>,
+            ),
+            "17" => (
+                process_event_data::<Event17>,
+                Store::store_page::<Event17, PipeCreatedEvent>,
+            ),
+            "18" => (
+                process_event_data::<Event18>,
+                Store::store_page::<Event18, PipeConnectedEvent>,
+            ),
+            "19" => (
+                process_event_data::<Event19>,
+                Store::store_page::<Event19, WmiEventFilterEvent>,
+            ),
+            "20" => (
+                process_event_data::<Event20>,
+                Store::store_page::<Event20, WmiEventConsumerEvent>,
+            ),
+            "21" => (
+                process_event_data::<Event21>,
+                Store::store_page::<Event21, WmiEventConsumerToFilterEvent>,
+            ),
+            "22" => (
+                process_event_data::<Event22>,
+                Store::store_page::<Event22, DnsQueryEvent>,
+            ),
+            "23" => (
+                process_event_data::<Event23>,
+                Store::store_page::<Event23, FileDeleteArchivedEvent>,
+            ),
+            "24" => (
+                process_event_data::<Event24>,
+                Store::store_page::<Event24, ClipboardChangedEvent>,
+            ),
+            "25" => (
+                process_event_data::<Event25>,
+                Store::store_page::<Event25, ProcessTamperingEvent>,
+            ),
+            "26" => (
+                process_event_data::<Event26>,
+                Store::store_page::<Event26, FileDeleteLoggedEvent>,
+            ),
+            "27" => (
+                process_event_data::<Event27>,
+                Store::store_page::<Event27, FileBlockExecutableEvent>,
+            ),
+            "28" => (
+                process_event_data::<Event28>,
+                Store::store_page::<Event28, FileBlockShreddingEvent>,
+            ),
+            "29" => (
+                process_event_data::<Event29>,
+                Store::store_page::<Event29, FileExecutableDetectedEvent>,
+            ),
+            _ => return,
+        };
+        let filename = format!(
+            "{}{}{}",
+            config.csv.save_location, event_code, config.csv.csv_name
+        );
+
+        // call `fetch_data_from_es` per event code, every page is processed then saved to RocksDB and/or file
+        // RocksDB first: reloading a page is idempotent there, appending it to the CSV again is not,
+        // so a store failure stops before the CSV and the next run fetches the page again from the mark
         let runtime = tokio::runtime::Runtime::new().unwrap();
         runtime.block_on(async {
-            match fetch_data_from_es(event_code).await {
-                Ok(datas) => {
-                    let filename = format!("{}{}{}", SAVELOCATION, event_code, CSVNAME);
-                    println!("Event {}", event_code);
-                    for data in &datas {
-                        match event_code {
-                            // call 'process_event_data' for processing each format
-                            "1" => process_event_data::<Event1>(data, &filename, SIZE),
-                            "2" => process_event_data::<Event2>(data, &filename, SIZE),
-                            "3" => process_event_data::<Event3>(data, &filename, SIZE),
-                            "5" => process_event_data::<Event5>(data, &filename, SIZE),
-                            "7" => process_event_data::<Event7>(data, &filename, SIZE),
-                            "9" => process_event_data::<Event9>(data, &filename, SIZE),
-                            "10" => process_event_data::<Event10>(data, &filename, SIZE),
-                            "11" => process_event_data::<Event11>(data, &filename, SIZE),
-                            "12" => process_event_data::<Event12>(data, &filename, SIZE),
-                            "13" => process_event_data::<Event13>(data, &filename, SIZE),
-                            "14" => process_event_data::<Event14>(data, &filename, SIZE),
-                            "15" => process_event_data::<Event15>(data, &filename, SIZE),
-                            "17" => process_event_data::<Event17>(data, &filename, SIZE),
-                            "18" => process_event_data::<Event18>(data, &filename, SIZE),
-                            "22" => process_event_data::<Event22>(data, &filename, SIZE),
-                            "23" => process_event_data::<Event23>(data, &filename, SIZE),
-                            "25" => process_event_data::<Event25>(data, &filename, SIZE),
-                            "26" => process_event_data::<Event26>(data, &filename, SIZE),
-                            _ => continue,
-                        }
+            let result = fetch_data_from_es(&config.elasticsearch, &state, event_code, |data| {
+                println!("Event {}", event_code);
+                if let Some(db) = &db {
+                    let counts = store_page(db, data)?;
+                    for reason in &counts.rejected {
+                        eprintln!("Rejected event {}: {}", event_code, reason);
                     }
+                    println!(
+                        "Stored in RocksDB: {}, already stored: {}, rejected: {}",
+                        counts.stored,
+                        counts.unchanged,
+                        counts.rejected.len()
+                    );
+                }
+                if config.output.csv {
+                    process_page(data, &filename, config.elasticsearch.size)?;
                 }
-                Err(err) => eprintln!("Error: {:?}", err),
+                Ok(())
+            })
+            .await;
+            if let Err(err) = result {
+                eprintln!("Error: {:?}", err);
             }
         });
     });
@@ -0,0 +1,39 @@
+use elarocks::envs::config::{Config, Needs};
+use elarocks::storage::migrate::{migrate_family, migrate_legacy};
+use elarocks::structs::events::EVENT_TYPES;
+use elarocks::Store;
+use std::env;
+use std::error::Error;
+
+// Rewrite every value of rocksdb.db_location with rocksdb.value_codec and write its index entries,
+// rows of older string-keyed databases are moved into the event families (see storage/migrate.rs)
+fn main() -> Result<(), Box<dyn Error>> {
+    let (settings, _) = Config::from_args(env::args(), Needs::Read)?;
+    let store = Store::open(&settings.rocksdb.db_location, settings.rocksdb.value_codec)?;
+
+    // rows of the first `rocks` (string keys, JSON) are moved into the event families first
+    let counts = migrate_legacy(&store)?;
+    if counts.rewritten + counts.failed > 0 {
+        println!(
+            "string keys: {} events moved as {}, {} failed",
+            counts.rewritten,
+            store.codec().name(),
+            counts.failed
+        );
+    }
+
+    for (code, name) in EVENT_TYPES {
+        let counts = migrate_family(&store, *code, name)?;
+        println!(
+            "{}: {} values rewritten as {}, {} unchanged, {} indexed, {} failed",
+            name,
+            counts.rewritten,
+            store.codec().name(),
+            counts.unchanged,
+            counts.indexed,
+            counts.failed
+        );
+    }
+
+    Ok(())
+}
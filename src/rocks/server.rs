@@ -0,0 +1,135 @@
+use axum::extract::{Query as Params, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use elarocks::envs::config::{Config, Needs};
+use elarocks::graphql::schema::{schema, EventSchema};
+use elarocks::storage::families::open_secondary;
+use elarocks::{FilterArgs, Pagination, Query};
+use rocksdb::DB;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::env;
+use std::error::Error;
+use std::sync::Arc;
+
+// Long-running query server, `nano-select` over HTTP with the db kept open
+//
+// GET /select?start_key=<event name_datetime>&end_key=...&direction=first|last&count=10&cursor=<cursor>
+//             &image=...&process_id=...&destination_ip=10.0.0.0/8&hash=SHA256=... (filter names of `FilterArgs`)
+//             &total_count=true (a pass over the whole window, total_count is null without it)
+// response: one array, {"cursor", "node"} per event then the page info (`Page::to_json`)
+// errors  : {"error": "..."}, 400 for invalid parameters, 500 for the db
+//
+// POST /graphql : the schema of graphql/src/schema/typeDefs.js for every event type (src/graphql/schema.rs)
+// GET  /graphql : GraphiQL
+//
+// the db is opened as a secondary instance: `rocks` / `main` keep writing it, every request first catches up
+// with their writes (its own files are in rocksdb.db_location + ".secondary")
+#[derive(Deserialize)]
+struct SelectParams {
+    start_key: String,
+    end_key: String,
+    direction: Option<String>,
+    // text, numbers of a flattened query string are not parsed by serde
+    count: Option<String>,
+    cursor: Option<String>,
+    total_count: Option<String>,
+    #[serde(flatten)]
+    filters: FilterArgs,
+}
+
+type Failure = (StatusCode, String);
+
+fn bad_request(e: Box<dyn Error>) -> Failure {
+    (StatusCode::BAD_REQUEST, e.to_string())
+}
+
+fn db_error(e: impl std::fmt::Display) -> Failure {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn run_select(db: &DB, params: &SelectParams) -> Result<Value, Failure> {
+    let count = match params.count.as_deref().filter(|count| !count.is_empty()) {
+        Some(count) => count
+            .parse::<usize>()
+            .map_err(|e| bad_request(format!("count: {}", e).into()))?,
+        None => usize::MAX,
+    };
+    let pagination =
+        Pagination::from_direction(params.direction.as_deref(), count, params.cursor.as_deref())
+            .map_err(bad_request)?;
+    let total_count = match params
+        .total_count
+        .as_deref()
+        .filter(|flag| !flag.is_empty())
+    {
+        Some(flag) => flag
+            .parse::<bool>()
+            .map_err(|e| bad_request(format!("total_count: {}", e).into()))?,
+        None => false,
+    };
+    let filters = params.filters.filters().map_err(bad_request)?;
+    let query = Query::from_search_keys(&params.start_key, &params.end_key)
+        .map_err(bad_request)?
+        .filter(filters);
+
+    db.try_catch_up_with_primary().map_err(db_error)?;
+    let page = query.page(db, &pagination).map_err(db_error)?;
+    let total_count = if total_count {
+        Some(query.count(db).map_err(db_error)?)
+    } else {
+        None
+    };
+    Ok(page.to_json(total_count))
+}
+
+async fn select(State(db): State<Arc<DB>>, Params(params): Params<SelectParams>) -> Response {
+    // RocksDB reads block, they run off the async workers
+    let result = tokio::task::spawn_blocking(move || run_select(&db, &params))
+        .await
+        .unwrap_or_else(|e| Err(db_error(e)));
+    match result {
+        Ok(page) => Json(page).into_response(),
+        Err((status, message)) => (status, Json(json!({ "error": message }))).into_response(),
+    }
+}
+
+async fn graphql(
+    Extension(schema): Extension<EventSchema>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(schema.execute(request).await)
+}
+
+async fn graphiql() -> Html<String> {
+    Html(
+        async_graphql::http::GraphiQLSource::build()
+            .endpoint("/graphql")
+            .finish(),
+    )
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let (settings, _) = Config::from_args(env::args(), Needs::Read)?;
+    let db_location = &settings.rocksdb.db_location;
+    let db = Arc::new(open_secondary(
+        db_location,
+        &format!("{}.secondary", db_location),
+    )?);
+
+    let app = Router::new()
+        .route("/select", get(select))
+        .route("/graphql", get(graphiql).post(graphql))
+        .layer(Extension(schema(db.clone())))
+        .with_state(db);
+    let listener = tokio::net::TcpListener::bind(&settings.server.listen).await?;
+    println!(
+        "Serving {} on http://{}",
+        db_location, settings.server.listen
+    );
+    axum::serve(listener, app).await?;
+    Ok(())
+}
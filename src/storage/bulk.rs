@@ -0,0 +1,110 @@
+use super::families::family_options;
+use super::keys::KEY_LEN;
+use super::store::{family, EventDb, Put};
+use super::values::same_event;
+use rocksdb::{
+    ColumnFamily, IngestExternalFileOptions, IteratorMode, SstFileWriter, WriteBatchWithTransaction,
+};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Bulk load for backfills (`rocks --bulk`): sort by encoded key, write SST files, ingest them in one call
+// ingested files skip the memtable and most of the compaction a put of every event would cause
+// the whole input is held in memory while sorting, split archives larger than RAM into several files
+
+// events per SST file
+const ROWS_PER_SST: usize = 1_000_000;
+// index entries per write batch after the ingest
+const INDEX_BATCH: usize = 100_000;
+
+// encoded key, encoded value, (index family, index key) of the event
+pub type BulkEntry = ([u8; KEY_LEN], Vec<u8>, Vec<(&'static str, Vec<u8>)>);
+
+#[derive(Default)]
+pub struct BulkCounts {
+    pub stored: usize,
+    pub unchanged: usize,
+    pub conflicts: usize,
+}
+
+impl BulkCounts {
+    fn count(&mut self, put: Put) {
+        match put {
+            Put::Stored => self.stored += 1,
+            Put::Unchanged => self.unchanged += 1,
+            Put::Conflict => self.conflicts += 1,
+        }
+    }
+}
+
+// SST files are written to `work_dir` and moved into the db by the ingest, the directory is removed afterwards
+// like `EventBatch`, a key already stored (in the db or earlier in `entries`) is never replaced
+pub(crate) fn bulk_load(
+    db: &EventDb,
+    cf: &ColumnFamily,
+    family_name: &str,
+    mut entries: Vec<BulkEntry>,
+    work_dir: &Path,
+) -> Result<BulkCounts, Box<dyn Error>> {
+    let mut counts = BulkCounts::default();
+    // stable sort, the first of equal keys is the one kept
+    entries.sort_by_key(|(key, _, _)| *key);
+
+    // lookups are only needed when the family already has events (not an initial load)
+    let check_db = db.iterator_cf(cf, IteratorMode::Start).next().is_some();
+
+    let mut unique: Vec<BulkEntry> = Vec::with_capacity(entries.len());
+    for (key, value, index) in entries {
+        let stored = match unique.last() {
+            Some((last_key, last_value, _)) if *last_key == key => Some(last_value.clone()),
+            _ if check_db => db.get_cf(cf, key)?,
+            _ => None,
+        };
+        match stored {
+            Some(stored) if same_event(&stored, &value) => counts.count(Put::Unchanged),
+            Some(_) => counts.count(Put::Conflict),
+            None => {
+                counts.count(Put::Stored);
+                unique.push((key, value, index));
+            }
+        }
+    }
+    if unique.is_empty() {
+        return Ok(counts);
+    }
+
+    fs::create_dir_all(work_dir)?;
+    let opts = family_options(family_name);
+    let mut files: Vec<PathBuf> = Vec::new();
+    for (number, chunk) in unique.chunks(ROWS_PER_SST).enumerate() {
+        let path = work_dir.join(format!("{}.sst", number));
+        let mut writer = SstFileWriter::create(&opts);
+        writer.open(&path)?;
+        for (key, value, _) in chunk {
+            writer.put(key, value)?;
+        }
+        writer.finish()?;
+        files.push(path);
+    }
+
+    // every file or none, the ingest is one version edit of the db
+    let mut ingest_options = IngestExternalFileOptions::default();
+    ingest_options.set_move_files(true);
+    let result = db.ingest_external_file_cf_opts(cf, &ingest_options, files);
+    fs::remove_dir_all(work_dir)?;
+    result?;
+
+    // indexes are written after the events, a crash in between leaves events without index entries
+    // (`migrate` writes them again)
+    let mut batch = WriteBatchWithTransaction::<true>::default();
+    for (family_name, index_key) in unique.iter().flat_map(|(_, _, index)| index) {
+        batch.put_cf(family(db, family_name)?, index_key, []);
+        if batch.len() == INDEX_BATCH {
+            db.write(std::mem::take(&mut batch))?;
+        }
+    }
+    db.write(batch)?;
+
+    Ok(counts)
+}
@@ -0,0 +1,88 @@
+use super::index::is_index_family;
+use super::keys::TIME_PREFIX_LEN;
+use rocksdb::{
+    BlockBasedOptions, ColumnFamilyDescriptor, DBCompressionType, Options, ReadOptions,
+    SliceTransform, DB,
+};
+
+// One column family per Sysmon event type, named after its event.action (e.g. "Process Create")
+// and one per secondary index ("index image", see storage/index.rs)
+// keys are the binary `EventKey` of storage/keys.rs, a scan of one family never reads another type
+//
+// per family options:
+// high volume types : zstd, 64KB blocks (compaction cost is paid once, scans read fewer blocks)
+// other types       : lz4, 16KB blocks
+// event families    : prefix extractor on key version, type id and the high 4 bytes of the timestamp (~4.3 second buckets)
+//                     with a bloom filter of those prefixes in the memtable and in every table file
+//
+// the bloom filters serve point lookups: `get` of a key skips every table (and the memtable) whose filter does not
+// have its prefix, which is what the loaders do for every event (idempotent reloads, `EventBatch::put`, bulk loads)
+// and the index scans for every matching key. a time window spans many buckets, range scans seek in total order
+// (`scan_options`) and do not use the filters
+const HIGH_VOLUME_FAMILIES: [&str; 8] = [
+    "Process Create",
+    "Network connection detected",
+    "Image loaded",
+    "Process accessed",
+    "File created",
+    "Registry object added or deleted",
+    "Registry value set",
+    "Dns query",
+];
+
+pub fn family_options(name: &str) -> Options {
+    let mut block_options = BlockBasedOptions::default();
+    let mut opts = Options::default();
+    // index keys start with a field value of any length, no prefix extractor
+    if is_index_family(name) {
+        opts.set_compression_type(DBCompressionType::Lz4);
+        block_options.set_block_size(16 * 1024);
+        opts.set_block_based_table_factory(&block_options);
+        return opts;
+    }
+    if HIGH_VOLUME_FAMILIES.contains(&name) {
+        opts.set_compression_type(DBCompressionType::Zstd);
+        block_options.set_block_size(64 * 1024);
+    } else {
+        opts.set_compression_type(DBCompressionType::Lz4);
+        block_options.set_block_size(16 * 1024);
+    }
+    // 10 bits per prefix (~1% false positives), whole keys are added too (default)
+    block_options.set_bloom_filter(10.0, false);
+    opts.set_block_based_table_factory(&block_options);
+    opts.set_prefix_extractor(SliceTransform::create_fixed_prefix(TIME_PREFIX_LEN));
+    opts.set_memtable_prefix_bloom_ratio(0.1);
+    opts
+}
+
+// Range scans cross prefix extractor buckets, iterators must not stop at the first prefix (nor skip tables
+// by their prefix filters)
+pub fn scan_options() -> ReadOptions {
+    let mut read_options = ReadOptions::default();
+    read_options.set_total_order_seek(true);
+    read_options
+}
+
+// Open every column family already in the database (readers do not create families)
+pub fn open_families(db_path: &str) -> Result<DB, rocksdb::Error> {
+    let names = DB::list_cf(&Options::default(), db_path)?;
+    let families = names.into_iter().map(|name| {
+        let opts = family_options(&name);
+        (name, opts)
+    });
+    DB::open_cf_with_opts(&Options::default(), db_path, families)
+}
+
+// Read-only follower of a db another process writes (`server`), the writer's lock is not taken
+// `secondary_path` keeps the follower's own logs, `DB::try_catch_up_with_primary` reads the new writes
+pub fn open_secondary(db_path: &str, secondary_path: &str) -> Result<DB, rocksdb::Error> {
+    let mut opts = Options::default();
+    // a secondary instance keeps every table file open
+    opts.set_max_open_files(-1);
+    let names = DB::list_cf(&Options::default(), db_path)?;
+    let families = names.into_iter().map(|name| {
+        let opts = family_options(&name);
+        ColumnFamilyDescriptor::new(name, opts)
+    });
+    DB::open_cf_descriptors_as_secondary(&opts, db_path, secondary_path, families)
+}
@@ -0,0 +1,871 @@
+use super::families::scan_options;
+use super::keys::KEY_LEN;
+use super::values::decode_event;
+use rocksdb::{ColumnFamily, Direction, IteratorMode, DB};
+use serde::Deserialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::error::Error;
+use std::net::IpAddr;
+
+// Secondary indexes, one column family per field shared by every event type
+//
+// text index key  : field value, 0x00, primary key (26 bytes, event type is in the key) / empty value
+// fixed index key : field value (ip 16 bytes, port 2 bytes, big-endian), primary key / empty value
+//
+// entries of one value are in primary key order, a (value, time window) lookup is one seek then one step per key read
+// fixed width values sort like numbers, a CIDR block or port range is one seek per distinct value in it,
+// the keys of its values are merged into primary key order as the page reads them
+// written by the loaders next to the event (`EventBatch`, `rocks --bulk`, `migrate` for older databases)
+#[derive(Clone, Copy)]
+pub enum IndexKind {
+    Text,
+    // host and query names, lowercase (DNS names are case insensitive)
+    NoCase,
+    // v4 stored as v4-mapped v6, one family holds both
+    Ip,
+    Port,
+}
+
+pub struct Index {
+    pub field: &'static str,
+    pub family: &'static str,
+    pub kind: IndexKind,
+}
+
+const fn index(field: &'static str, family: &'static str, kind: IndexKind) -> Index {
+    Index {
+        field,
+        family,
+        kind,
+    }
+}
+
+pub const INDEXES: [Index; 14] = [
+    index("image", "index image", IndexKind::Text),
+    index("process_id", "index process_id", IndexKind::Text),
+    index("process_guid", "index process_guid", IndexKind::Text),
+    index(
+        "parent_process_guid",
+        "index parent_process_guid",
+        IndexKind::Text,
+    ),
+    index("agent_id", "index agent_id", IndexKind::Text),
+    index("user", "index user", IndexKind::Text),
+    // Network connection detected (Event3)
+    index("destination_ip", "index destination_ip", IndexKind::Ip),
+    index(
+        "destination_port",
+        "index destination_port",
+        IndexKind::Port,
+    ),
+    index(
+        "destination_hostname",
+        "index destination_hostname",
+        IndexKind::NoCase,
+    ),
+    // Dns query (Event22)
+    index("query_name", "index query_name", IndexKind::NoCase),
+    // Process Create (Event1) and Image loaded (Event7), one family per hash algorithm
+    index("hashes.sha256", "index hashes.sha256", IndexKind::NoCase),
+    index("hashes.sha1", "index hashes.sha1", IndexKind::NoCase),
+    index("hashes.md5", "index hashes.md5", IndexKind::NoCase),
+    index("hashes.imphash", "index hashes.imphash", IndexKind::NoCase),
+];
+
+// filters use the first index of this list that has a filter, most selective first
+const PREFERENCE: [&str; 13] = [
+    "process_guid",
+    "parent_process_guid",
+    "hashes.sha256",
+    "hashes.sha1",
+    "hashes.md5",
+    "hashes.imphash",
+    "query_name",
+    "destination_hostname",
+    "destination_ip",
+    "process_id",
+    "destination_port",
+    "user",
+    "agent_id",
+];
+
+pub fn is_index_family(name: &str) -> bool {
+    INDEXES.iter().any(|index| index.family == name)
+}
+
+fn index_of(field: &str) -> Result<&'static Index, Box<dyn Error>> {
+    INDEXES
+        .iter()
+        .find(|index| index.field == field)
+        .ok_or_else(|| format!("{} is not indexed", field).into())
+}
+
+// field of an event, "a.b" is field b of the object in field a
+fn event_field<'a>(event: &'a Value, field: &str) -> &'a Value {
+    field.split('.').fold(event, |value, name| &value[name])
+}
+
+// indexed bytes of a field (text fields: numbers as decimal text), missing, empty or unparsable fields are not indexed
+fn field_value(kind: IndexKind, field: &Value) -> Option<Vec<u8>> {
+    match (kind, field) {
+        (IndexKind::Text, Value::String(text)) if !text.is_empty() => {
+            Some(text.as_bytes().to_vec())
+        }
+        (IndexKind::Text, Value::Number(number)) => Some(number.to_string().into_bytes()),
+        (IndexKind::NoCase, Value::String(text)) if !text.is_empty() => {
+            Some(text.to_lowercase().into_bytes())
+        }
+        (IndexKind::Ip, Value::String(text)) => Some(ip_bytes(text.parse().ok()?).to_vec()),
+        (IndexKind::Port, Value::String(text)) => {
+            Some(text.trim().parse::<u16>().ok()?.to_be_bytes().to_vec())
+        }
+        (IndexKind::Port, Value::Number(number)) => {
+            let port = u16::try_from(number.as_u64()?).ok()?;
+            Some(port.to_be_bytes().to_vec())
+        }
+        _ => None,
+    }
+}
+
+fn ip_bytes(ip: IpAddr) -> [u8; 16] {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped().octets(),
+        IpAddr::V6(ip) => ip.octets(),
+    }
+}
+
+// index key without the primary key
+fn value_prefix(kind: IndexKind, value: &[u8]) -> Vec<u8> {
+    let mut prefix = value.to_vec();
+    if let IndexKind::Text | IndexKind::NoCase = kind {
+        prefix.push(0);
+    }
+    prefix
+}
+
+// (index family, index key) of every indexed field the event has
+pub fn index_entries(event: &Value, primary_key: &[u8; KEY_LEN]) -> Vec<(&'static str, Vec<u8>)> {
+    INDEXES
+        .iter()
+        .filter_map(|index| {
+            let value = field_value(index.kind, event_field(event, index.field))?;
+            let mut key = value_prefix(index.kind, &value);
+            key.extend_from_slice(primary_key);
+            Some((index.family, key))
+        })
+        .collect()
+}
+
+// Fields filters read, decoded from a stored value without the rest of the event (every indexed field)
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct FilterFields {
+    image: Value,
+    process_id: Value,
+    process_guid: Value,
+    parent_process_guid: Value,
+    agent_id: Value,
+    user: Value,
+    destination_ip: Value,
+    destination_port: Value,
+    destination_hostname: Value,
+    query_name: Value,
+    hashes: Value,
+}
+
+static NULL: Value = Value::Null;
+
+impl FilterFields {
+    // like `event_field`, null when the event has no such field
+    fn field(&self, field: &str) -> &Value {
+        let (name, nested) = match field.split_once('.') {
+            Some((name, nested)) => (name, Some(nested)),
+            None => (field, None),
+        };
+        let value = match name {
+            "image" => &self.image,
+            "process_id" => &self.process_id,
+            "process_guid" => &self.process_guid,
+            "parent_process_guid" => &self.parent_process_guid,
+            "agent_id" => &self.agent_id,
+            "user" => &self.user,
+            "destination_ip" => &self.destination_ip,
+            "destination_port" => &self.destination_port,
+            "destination_hostname" => &self.destination_hostname,
+            "query_name" => &self.query_name,
+            "hashes" => &self.hashes,
+            _ => &NULL,
+        };
+        match nested {
+            Some(nested) => event_field(value, nested),
+            None => value,
+        }
+    }
+}
+
+// indexed value of a field between low and high (inclusive), exact filters have low == high
+struct Condition {
+    index: &'static Index,
+    low: Vec<u8>,
+    high: Vec<u8>,
+}
+
+// Filters of `select` / `nano-select`, empty strings mean no filter
+#[derive(Default)]
+pub struct Filters {
+    image_contains: Option<String>,
+    conditions: Vec<Condition>,
+}
+
+impl Filters {
+    pub fn image_contains(mut self, image: Option<&str>) -> Filters {
+        self.image_contains = image.filter(|image| !image.is_empty()).map(str::to_string);
+        self
+    }
+
+    // exact value of a text or name field
+    pub fn exact(mut self, field: &'static str, value: Option<&str>) -> Filters {
+        let Some(value) = value.filter(|value| !value.is_empty()) else {
+            return self;
+        };
+        let Ok(index) = index_of(field) else {
+            return self;
+        };
+        if let Some(value) = field_value(index.kind, &Value::String(value.to_string())) {
+            self.conditions.push(Condition {
+                index,
+                low: value.clone(),
+                high: value,
+            });
+        }
+        self
+    }
+
+    // "10.1.2.3", "10.0.0.0/8", "fe80::/10"
+    pub fn ip_range(
+        mut self,
+        field: &'static str,
+        cidr: Option<&str>,
+    ) -> Result<Filters, Box<dyn Error>> {
+        let Some(cidr) = cidr.filter(|cidr| !cidr.is_empty()) else {
+            return Ok(self);
+        };
+        let (address, prefix_len) = match cidr.split_once('/') {
+            Some((address, prefix_len)) => (address, Some(prefix_len.parse::<u32>()?)),
+            None => (cidr, None),
+        };
+        let ip: IpAddr = address
+            .parse()
+            .map_err(|e| format!("{}: invalid ip {}: {}", field, address, e))?;
+        // v4 prefix lengths count from the v4 part of the mapped address
+        let (max_len, mapped_bits) = match ip {
+            IpAddr::V4(_) => (32, 96),
+            IpAddr::V6(_) => (128, 0),
+        };
+        let prefix_len = prefix_len.unwrap_or(max_len);
+        if prefix_len > max_len {
+            return Err(format!("{}: invalid prefix length in {}", field, cidr).into());
+        }
+        let bits = u128::from_be_bytes(ip_bytes(ip));
+        let host_mask = u128::MAX.checked_shr(mapped_bits + prefix_len).unwrap_or(0);
+        self.conditions.push(Condition {
+            index: index_of(field)?,
+            low: (bits & !host_mask).to_be_bytes().to_vec(),
+            high: (bits | host_mask).to_be_bytes().to_vec(),
+        });
+        Ok(self)
+    }
+
+    // "443" or "1024-2048"
+    pub fn port_range(
+        mut self,
+        field: &'static str,
+        ports: Option<&str>,
+    ) -> Result<Filters, Box<dyn Error>> {
+        let Some(ports) = ports.filter(|ports| !ports.is_empty()) else {
+            return Ok(self);
+        };
+        let (low, high) = ports.split_once('-').unwrap_or((ports, ports));
+        let parse = |port: &str| {
+            port.trim()
+                .parse::<u16>()
+                .map_err(|e| format!("{}: invalid port range {}: {}", field, ports, e))
+        };
+        let (low, high) = (parse(low)?, parse(high)?);
+        self.conditions.push(Condition {
+            index: index_of(field)?,
+            low: low.to_be_bytes().to_vec(),
+            high: high.to_be_bytes().to_vec(),
+        });
+        Ok(self)
+    }
+
+    // "SHA256=...", "IMPHASH=..." (algorithm and digest in any case), like the Sysmon Hashes field
+    pub fn hash(self, hash: Option<&str>) -> Result<Filters, Box<dyn Error>> {
+        let Some(hash) = hash.filter(|hash| !hash.is_empty()) else {
+            return Ok(self);
+        };
+        let (algorithm, digest) = hash
+            .split_once('=')
+            .ok_or_else(|| format!("hash: expected ALGORITHM=digest, got {}", hash))?;
+        let field = match algorithm.trim().to_ascii_uppercase().as_str() {
+            "SHA256" => "hashes.sha256",
+            "SHA1" => "hashes.sha1",
+            "MD5" => "hashes.md5",
+            "IMPHASH" => "hashes.imphash",
+            _ => return Err(format!("hash: unknown algorithm {}", algorithm).into()),
+        };
+        Ok(self.exact(field, Some(digest.trim())))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.image_contains.is_none() && self.conditions.is_empty()
+    }
+
+    fn matches(&self, event: &FilterFields) -> bool {
+        let image = self.image_contains.as_ref().is_none_or(|needle| {
+            event
+                .image
+                .as_str()
+                .is_some_and(|image| image.contains(needle.as_str()))
+        });
+        image
+            && self.conditions.iter().all(|condition| {
+                field_value(condition.index.kind, event.field(condition.index.field))
+                    .is_some_and(|value| condition.low <= value && value <= condition.high)
+            })
+    }
+
+    // a stored value passes the filters, only decoded when there is one
+    fn keep(&self, value: &[u8]) -> Result<bool, Box<dyn Error>> {
+        if self.is_empty() {
+            return Ok(true);
+        }
+        Ok(self.matches(&decode_event::<FilterFields>(value)?))
+    }
+
+    // primary keys between lower and upper (inclusive) found by an index, in `direction` order
+    // read from the index while the caller takes them, a page stops after its last event
+    // None when there is no filter or the db has no index family for it (full scan)
+    fn indexed_keys<'a>(
+        &self,
+        db: &'a DB,
+        lower: &[u8],
+        upper: &[u8],
+        direction: Direction,
+    ) -> Result<Option<Keys<'a>>, Box<dyn Error>> {
+        let condition = PREFERENCE.iter().find_map(|field| {
+            self.conditions
+                .iter()
+                .find(|condition| condition.index.field == *field)
+        });
+        if let Some(condition) = condition {
+            let Some(cf) = db.cf_handle(condition.index.family) else {
+                return Ok(None);
+            };
+            if condition.low == condition.high {
+                let prefix = value_prefix(condition.index.kind, &condition.low);
+                return Ok(Some(value_keys(db, cf, prefix, lower, upper, direction)));
+            }
+            let prefixes = range_prefixes(db, cf, condition, lower, upper)?;
+            return Ok(Some(merge(db, cf, prefixes, lower, upper, direction)?));
+        }
+
+        let Some(needle) = &self.image_contains else {
+            return Ok(None);
+        };
+        let Some(cf) = db.cf_handle(index_of("image")?.family) else {
+            return Ok(None);
+        };
+        // skip scan: one seek per distinct image path, only paths containing the filter are read
+        let mut prefixes = Vec::new();
+        let mut next: Vec<u8> = Vec::new();
+        while let Some(key) = first_key(db, cf, &next)? {
+            let Some(end) = key.iter().position(|byte| *byte == 0) else {
+                break;
+            };
+            let value = &key[..end];
+            if String::from_utf8_lossy(value).contains(needle.as_str())
+                && has_keys(db, cf, &key[..=end], lower, upper)?
+            {
+                prefixes.push(key[..=end].to_vec());
+            }
+            // first key after every entry of this value (0x01 sorts right after the 0x00 separator)
+            next = value.to_vec();
+            next.push(1);
+        }
+        Ok(Some(merge(db, cf, prefixes, lower, upper, direction)?))
+    }
+
+    // the index `indexed_keys` reads answers every filter, its keys need no event to be checked
+    fn answered_by_index(&self) -> bool {
+        self.conditions.len() + usize::from(self.image_contains.is_some()) == 1
+    }
+}
+
+fn first_key(db: &DB, cf: &ColumnFamily, from: &[u8]) -> Result<Option<Box<[u8]>>, Box<dyn Error>> {
+    let mut iter = db.iterator_cf_opt(
+        cf,
+        scan_options(),
+        IteratorMode::From(from, Direction::Forward),
+    );
+    match iter.next() {
+        Some(item) => Ok(Some(item?.0)),
+        None => Ok(None),
+    }
+}
+
+// primary key part of an index key of `prefix` when it is between lower and upper
+fn primary_key<'k>(key: &'k [u8], prefix: &[u8], lower: &[u8], upper: &[u8]) -> Option<&'k [u8]> {
+    if key.len() != prefix.len() + KEY_LEN || !key.starts_with(prefix) {
+        return None;
+    }
+    let primary_key = &key[prefix.len()..];
+    (lower <= primary_key && primary_key <= upper).then_some(primary_key)
+}
+
+// an indexed value (`value_prefix`) has an event between lower and upper
+fn has_keys(
+    db: &DB,
+    cf: &ColumnFamily,
+    prefix: &[u8],
+    lower: &[u8],
+    upper: &[u8],
+) -> Result<bool, Box<dyn Error>> {
+    let mut from = prefix.to_vec();
+    from.extend_from_slice(lower);
+    Ok(first_key(db, cf, &from)?
+        .is_some_and(|key| primary_key(&key, prefix, lower, upper).is_some()))
+}
+
+pub type Keys<'a> = Box<dyn Iterator<Item = Result<Vec<u8>, Box<dyn Error>>> + 'a>;
+
+// primary keys of one indexed value (`value_prefix`) between lower and upper, in `direction` order
+// entries of one value are in primary key order, one seek then one step per key
+fn value_keys<'a>(
+    db: &'a DB,
+    cf: &'a ColumnFamily,
+    prefix: Vec<u8>,
+    lower: &[u8],
+    upper: &[u8],
+    direction: Direction,
+) -> Keys<'a> {
+    let mut from = prefix.clone();
+    from.extend_from_slice(match direction {
+        Direction::Forward => lower,
+        Direction::Reverse => upper,
+    });
+    let (lower, upper) = (lower.to_vec(), upper.to_vec());
+    let iter = db.iterator_cf_opt(cf, scan_options(), IteratorMode::From(&from, direction));
+    Box::new(iter.map_while(move |item| match item {
+        Ok((key, _)) => primary_key(&key, &prefix, &lower, &upper).map(|key| Ok(key.to_vec())),
+        Err(e) => Some(Err(e.into())),
+    }))
+}
+
+// skip scan over the fixed width values of a range: seek to (value, lower), keep the value when it has an event
+// in the window, then seek to (value + 1, lower), values without events in the window cost one seek
+fn range_prefixes(
+    db: &DB,
+    cf: &ColumnFamily,
+    condition: &Condition,
+    lower: &[u8],
+    upper: &[u8],
+) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+    let width = condition.low.len();
+    let mut prefixes = Vec::new();
+    let mut value = condition.low.clone();
+    loop {
+        let mut from = value.clone();
+        from.extend_from_slice(lower);
+        let Some(key) = first_key(db, cf, &from)? else {
+            break;
+        };
+        if key.len() != width + KEY_LEN || key[..width] > *condition.high {
+            break;
+        }
+        // landed on a later value, seek again to its time window
+        if key[..width] != *value {
+            value = key[..width].to_vec();
+            continue;
+        }
+        if primary_key(&key, &value, lower, upper).is_some() {
+            prefixes.push(value.clone());
+        }
+        if !increment(&mut value) {
+            break;
+        }
+    }
+    Ok(prefixes)
+}
+
+// big-endian + 1, false on overflow
+fn increment(value: &mut [u8]) -> bool {
+    for byte in value.iter_mut().rev() {
+        if *byte == u8::MAX {
+            *byte = 0;
+        } else {
+            *byte += 1;
+            return true;
+        }
+    }
+    false
+}
+
+// next key of one value in `Merge`, the heap pops the first key of the direction
+struct Head {
+    key: Vec<u8>,
+    stream: usize,
+    forward: bool,
+}
+
+impl Ord for Head {
+    fn cmp(&self, other: &Head) -> Ordering {
+        let order = self
+            .key
+            .cmp(&other.key)
+            .then(self.stream.cmp(&other.stream));
+        if self.forward {
+            order.reverse()
+        } else {
+            order
+        }
+    }
+}
+
+impl PartialOrd for Head {
+    fn partial_cmp(&self, other: &Head) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head {
+    fn eq(&self, other: &Head) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head {}
+
+// keys of several indexed values (CIDR block, port range, image paths) merged into one primary key order
+// one open iterator per value, a key is read when the previous one of its value was taken
+struct Merge<'a> {
+    streams: Vec<Keys<'a>>,
+    heads: BinaryHeap<Head>,
+    forward: bool,
+    // error of a stream, returned after the key taken with it
+    failed: Option<Box<dyn Error>>,
+}
+
+impl Merge<'_> {
+    fn pull(&mut self, stream: usize) -> Result<(), Box<dyn Error>> {
+        if let Some(key) = self.streams[stream].next().transpose()? {
+            self.heads.push(Head {
+                key,
+                stream,
+                forward: self.forward,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Merge<'_> {
+    type Item = Result<Vec<u8>, Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.failed.take() {
+            return Some(Err(e));
+        }
+        let head = self.heads.pop()?;
+        if let Err(e) = self.pull(head.stream) {
+            self.failed = Some(e);
+        }
+        Some(Ok(head.key))
+    }
+}
+
+fn merge<'a>(
+    db: &'a DB,
+    cf: &'a ColumnFamily,
+    prefixes: Vec<Vec<u8>>,
+    lower: &[u8],
+    upper: &[u8],
+    direction: Direction,
+) -> Result<Keys<'a>, Box<dyn Error>> {
+    let forward = matches!(direction, Direction::Forward);
+    let mut merge = Merge {
+        streams: prefixes
+            .into_iter()
+            .map(|prefix| value_keys(db, cf, prefix, lower, upper, direction))
+            .collect(),
+        heads: BinaryHeap::new(),
+        forward,
+        failed: None,
+    };
+    for stream in 0..merge.streams.len() {
+        merge.pull(stream)?;
+    }
+    Ok(Box::new(merge))
+}
+
+// primary key and stored value, decoded by the reader (`Query::page`)
+pub type Row = (Box<[u8]>, Box<[u8]>);
+pub type Rows<'a> = Box<dyn Iterator<Item = Result<Row, Box<dyn Error>>> + 'a>;
+
+// Events between lower and upper (inclusive primary keys) that match the filters, in `direction` order
+// an index is used when a filter has one, otherwise every value in the range is read (decoded only to check filters)
+pub fn scan<'a>(
+    db: &'a DB,
+    cf: &'a ColumnFamily,
+    filters: &'a Filters,
+    lower: &[u8],
+    upper: &[u8],
+    direction: Direction,
+) -> Result<Rows<'a>, Box<dyn Error>> {
+    if let Some(keys) = filters.indexed_keys(db, lower, upper, direction)? {
+        let answered = filters.answered_by_index();
+        return Ok(Box::new(keys.filter_map(move |key| {
+            let key = match key {
+                Ok(key) => key,
+                Err(e) => return Some(Err(e)),
+            };
+            let value = match db.get_cf(cf, &key) {
+                Ok(Some(value)) => value,
+                // index entry without event, nothing to return
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e.into())),
+            };
+            if answered {
+                return Some(Ok((key.into_boxed_slice(), value.into_boxed_slice())));
+            }
+            match filters.keep(&value) {
+                Ok(true) => Some(Ok((key.into_boxed_slice(), value.into_boxed_slice()))),
+                Ok(false) => None,
+                Err(e) => Some(Err(e)),
+            }
+        })));
+    }
+
+    let lower = lower.to_vec();
+    let upper = upper.to_vec();
+    let mode = match direction {
+        Direction::Forward => IteratorMode::From(&lower, Direction::Forward),
+        Direction::Reverse => IteratorMode::From(&upper, Direction::Reverse),
+    };
+    let iter = db.iterator_cf_opt(cf, scan_options(), mode);
+    Ok(Box::new(
+        iter.take_while(move |item| match item {
+            Ok((key, _)) => key.as_ref() >= lower.as_slice() && key.as_ref() <= upper.as_slice(),
+            Err(_) => true,
+        })
+        .filter_map(move |item| {
+            let (key, value) = match item {
+                Ok(item) => item,
+                Err(e) => return Some(Err(e.into())),
+            };
+            match filters.keep(&value) {
+                Ok(true) => Some(Ok((key, value))),
+                Ok(false) => None,
+                Err(e) => Some(Err(e)),
+            }
+        }),
+    ))
+}
+
+// matching events between lower and upper (inclusive)
+// keys of the index when it answers the filters, without reading the events, else the events without decoding
+// them when there is no filter
+pub fn count(
+    db: &DB,
+    cf: &ColumnFamily,
+    filters: &Filters,
+    lower: &[u8],
+    upper: &[u8],
+) -> Result<usize, Box<dyn Error>> {
+    if filters.answered_by_index() {
+        if let Some(mut keys) = filters.indexed_keys(db, lower, upper, Direction::Forward)? {
+            return keys.try_fold(0, |count, key| key.map(|_| count + 1));
+        }
+    }
+    scan(db, cf, filters, lower, upper, Direction::Forward)?
+        .try_fold(0, |count, row| row.map(|_| count + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merged(values: &[&[u8]], forward: bool) -> Vec<u8> {
+        let streams = values
+            .iter()
+            .map(|keys| {
+                let mut keys: Vec<u8> = keys.to_vec();
+                if !forward {
+                    keys.reverse();
+                }
+                Box::new(keys.into_iter().map(|key| Ok(vec![key]))) as Keys
+            })
+            .collect();
+        let mut merge = Merge {
+            streams,
+            heads: BinaryHeap::new(),
+            forward,
+            failed: None,
+        };
+        for stream in 0..values.len() {
+            merge.pull(stream).unwrap();
+        }
+        merge.map(|key| key.unwrap()[0]).collect()
+    }
+
+    fn range(filters: &Filters) -> (&[u8], &[u8]) {
+        let condition = &filters.conditions[0];
+        (&condition.low, &condition.high)
+    }
+
+    fn ip(text: &str) -> Vec<u8> {
+        ip_bytes(text.parse().unwrap()).to_vec()
+    }
+
+    #[test]
+    fn cidr_blocks() {
+        let filters = Filters::default()
+            .ip_range("destination_ip", Some("10.1.2.3/8"))
+            .unwrap();
+        assert_eq!(
+            range(&filters),
+            (&ip("10.0.0.0")[..], &ip("10.255.255.255")[..])
+        );
+        let filters = Filters::default()
+            .ip_range("destination_ip", Some("192.168.1.7"))
+            .unwrap();
+        assert_eq!(
+            range(&filters),
+            (&ip("192.168.1.7")[..], &ip("192.168.1.7")[..])
+        );
+        // /0 of v4 is every v4 address, not every address
+        let filters = Filters::default()
+            .ip_range("destination_ip", Some("0.0.0.0/0"))
+            .unwrap();
+        assert_eq!(
+            range(&filters),
+            (&ip("0.0.0.0")[..], &ip("255.255.255.255")[..])
+        );
+        let filters = Filters::default()
+            .ip_range("destination_ip", Some("fe80::1/10"))
+            .unwrap();
+        assert_eq!(
+            range(&filters),
+            (
+                &ip("fe80::")[..],
+                &ip("febf:ffff:ffff:ffff:ffff:ffff:ffff:ffff")[..]
+            )
+        );
+        let filters = Filters::default()
+            .ip_range("destination_ip", Some("::/0"))
+            .unwrap();
+        assert_eq!(range(&filters), (&[0; 16][..], &[0xff; 16][..]));
+
+        for cidr in ["10.0.0.0/33", "fe80::/129", "10.0.0/8", "10.0.0.0/x"] {
+            assert!(
+                Filters::default()
+                    .ip_range("destination_ip", Some(cidr))
+                    .is_err(),
+                "{}",
+                cidr
+            );
+        }
+        assert!(Filters::default()
+            .ip_range("destination_ip", Some(""))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn port_ranges() {
+        let filters = Filters::default()
+            .port_range("destination_port", Some("1024-2048"))
+            .unwrap();
+        assert_eq!(range(&filters), (&[4, 0][..], &[8, 0][..]));
+        let filters = Filters::default()
+            .port_range("destination_port", Some(" 443 "))
+            .unwrap();
+        assert_eq!(range(&filters), (&[1, 187][..], &[1, 187][..]));
+        for ports in ["65536", "http", "1-2-3"] {
+            assert!(
+                Filters::default()
+                    .port_range("destination_port", Some(ports))
+                    .is_err(),
+                "{}",
+                ports
+            );
+        }
+    }
+
+    #[test]
+    fn filter_fields_read_every_index() {
+        let event = serde_json::json!({
+            "image": "C:\\Windows\\System32\\cmd.exe",
+            "process_id": 6348,
+            "process_guid": "{d9d3b9b4-b5b1-64cf-8e05-000000000e00}",
+            "parent_process_guid": "{d9d3b9b4-b5a0-64cf-7c05-000000000e00}",
+            "agent_id": "7c8c1b8e",
+            "user": "DESKTOP-1\\user",
+            "destination_ip": "10.1.2.3",
+            "destination_port": 443,
+            "destination_hostname": "Example.COM",
+            "query_name": "Example.com",
+            "hashes": {"sha1": "A", "md5": "B", "sha256": "C", "imphash": "D"},
+            "command_line": "not read by filters",
+        });
+        let fields: FilterFields = serde_json::from_value(event.clone()).unwrap();
+        for index in &INDEXES {
+            assert_eq!(
+                fields.field(index.field),
+                event_field(&event, index.field),
+                "{}",
+                index.field
+            );
+            assert!(field_value(index.kind, fields.field(index.field)).is_some());
+        }
+
+        let filters = Filters::default()
+            .image_contains(Some("System32"))
+            .exact("process_id", Some("6348"))
+            .exact("destination_hostname", Some("example.com"))
+            .ip_range("destination_ip", Some("10.0.0.0/8"))
+            .unwrap()
+            .port_range("destination_port", Some("400-500"))
+            .unwrap()
+            .hash(Some("sha256=c"))
+            .unwrap();
+        assert!(filters.matches(&fields));
+        let other = Filters::default().exact("process_id", Some("6349"));
+        assert!(!other.matches(&fields));
+    }
+
+    #[test]
+    fn merge_keeps_primary_key_order() {
+        let values: [&[u8]; 3] = [&[1, 4, 9], &[2, 3], &[5, 6, 7, 8]];
+        assert_eq!(merged(&values, true), [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(merged(&values, false), [9, 8, 7, 6, 5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn merge_returns_a_stream_error_after_the_taken_key() {
+        let failing = vec![Ok(vec![1]), Err("read failed".into())].into_iter();
+        let mut merge = Merge {
+            streams: vec![Box::new(failing)],
+            heads: BinaryHeap::new(),
+            forward: true,
+            failed: None,
+        };
+        merge.pull(0).unwrap();
+        assert_eq!(merge.next().unwrap().unwrap(), [1]);
+        assert!(merge.next().unwrap().is_err());
+        assert!(merge.next().is_none());
+    }
+}
@@ -0,0 +1,216 @@
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use std::error::Error;
+
+// Binary RocksDB keys shared by `rocks` (CSV load), `main` (direct output), `select` and `nano-select`
+//
+// | version | type id | timestamp | agent hash | record hash |
+// |   1     |    1    |     8     |     8      |      8      |  26 bytes, integers big-endian
+//
+// type id     : Sysmon event.code
+// timestamp   : epoch nanoseconds with the sign bit flipped, negative times sort before positive ones
+// agent hash  : FNV-1a of agent.id, events of one agent at the same time stay next to each other
+// record hash : FNV-1a of ProcessGuid + RecordID (`EventToCSV::discriminator`), loading the same event again writes the same key
+// byte order of encoded keys is the (type id, timestamp, agent hash, record hash) order
+// version 1 (22 bytes, per-run sequence number instead of record hash) is not read, load those databases again
+pub const KEY_VERSION: u8 = 2;
+pub const KEY_LEN: usize = 26;
+// version, type id and the high 4 timestamp bytes, prefix extractor length of every event family
+pub const TIME_PREFIX_LEN: usize = 6;
+
+// search key format of `select` / `nano-select` arguments, e.g. "Process Create_2023-08-06 12:34:56.000000000"
+pub const SEARCH_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EventKey {
+    pub type_id: u8,
+    pub nanos: i64,
+    pub agent_hash: u64,
+    pub record_hash: u64,
+}
+
+impl EventKey {
+    // None when the time can not be written as nanoseconds (after year 2262)
+    pub fn new(
+        type_id: u8,
+        utc_time: &DateTime<Utc>,
+        agent_id: &str,
+        record_hash: u64,
+    ) -> Option<EventKey> {
+        Some(EventKey {
+            type_id,
+            nanos: utc_time.timestamp_nanos_opt()?,
+            agent_hash: agent_hash(agent_id),
+            record_hash,
+        })
+    }
+
+    // smallest and largest key at one time, range bounds of a time window
+    pub fn first_at(type_id: u8, nanos: i64) -> EventKey {
+        EventKey {
+            type_id,
+            nanos,
+            agent_hash: 0,
+            record_hash: 0,
+        }
+    }
+
+    pub fn last_at(type_id: u8, nanos: i64) -> EventKey {
+        EventKey {
+            type_id,
+            nanos,
+            agent_hash: u64::MAX,
+            record_hash: u64::MAX,
+        }
+    }
+
+    pub fn encode(&self) -> [u8; KEY_LEN] {
+        let mut key = [0u8; KEY_LEN];
+        key[0] = KEY_VERSION;
+        key[1] = self.type_id;
+        key[2..10].copy_from_slice(&((self.nanos as u64) ^ (1 << 63)).to_be_bytes());
+        key[10..18].copy_from_slice(&self.agent_hash.to_be_bytes());
+        key[18..26].copy_from_slice(&self.record_hash.to_be_bytes());
+        key
+    }
+
+    pub fn decode(key: &[u8]) -> Result<EventKey, Box<dyn Error>> {
+        if key.len() != KEY_LEN {
+            return Err(format!("key has {} bytes, expected {}", key.len(), KEY_LEN).into());
+        }
+        if key[0] != KEY_VERSION {
+            return Err(format!("unsupported key version {}", key[0]).into());
+        }
+        let timestamp = u64::from_be_bytes(key[2..10].try_into()?);
+        Ok(EventKey {
+            type_id: key[1],
+            nanos: (timestamp ^ (1 << 63)) as i64,
+            agent_hash: u64::from_be_bytes(key[10..18].try_into()?),
+            record_hash: u64::from_be_bytes(key[18..26].try_into()?),
+        })
+    }
+
+    pub fn utc_time(&self) -> DateTime<Utc> {
+        Utc.timestamp_nanos(self.nanos)
+    }
+
+    // printable form of a key (error messages), cursors are in storage/pagination.rs
+    pub fn to_hex(self) -> String {
+        self.encode().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+// FNV-1a 64 bit, stable across runs and builds (std hashers are not)
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+pub fn agent_hash(agent_id: &str) -> u64 {
+    fnv1a(FNV_OFFSET, agent_id.as_bytes())
+}
+
+// parts are separated by 0xff (never in UTF-8), ["ab", "c"] and ["a", "bc"] hash differently
+pub fn record_hash(parts: &[&str]) -> u64 {
+    parts.iter().fold(FNV_OFFSET, |hash, part| {
+        fnv1a(fnv1a(hash, part.as_bytes()), &[0xff])
+    })
+}
+
+// "Process Create_2023-08-06 12:34:56.000000000" → ("Process Create", epoch nanoseconds)
+pub fn parse_search_key(search_key: &str) -> Result<(String, i64), Box<dyn Error>> {
+    let (event_name, datetime) = search_key.split_once('_').ok_or_else(|| {
+        format!(
+            "Failed to extract event name and datetime from {}",
+            search_key
+        )
+    })?;
+    let naive_dt = NaiveDateTime::parse_from_str(datetime, SEARCH_TIME_FORMAT)?;
+    let nanos = Utc
+        .from_utc_datetime(&naive_dt)
+        .timestamp_nanos_opt()
+        .ok_or("Failed to convert datetime to nanoseconds")?;
+    Ok((event_name.to_string(), nanos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(type_id: u8, nanos: i64, agent_hash: u64, record_hash: u64) -> EventKey {
+        EventKey {
+            type_id,
+            nanos,
+            agent_hash,
+            record_hash,
+        }
+    }
+
+    #[test]
+    fn keys_round_trip() {
+        for key in [
+            key(1, 1_691_334_001_311_000_000, agent_hash("agent"), 7),
+            key(22, -1, 0, u64::MAX),
+            key(255, i64::MIN, u64::MAX, 0),
+            key(0, i64::MAX, 1, 1),
+        ] {
+            let encoded = key.encode();
+            assert_eq!(encoded[0], KEY_VERSION);
+            assert_eq!(EventKey::decode(&encoded).unwrap(), key);
+        }
+    }
+
+    #[test]
+    fn encoded_order_is_key_order() {
+        // negative times before positive ones, then agent and record hash
+        let mut keys = [
+            key(1, 5, 0, 0),
+            key(1, -5, 9, 9),
+            key(3, i64::MIN, 0, 0),
+            key(1, 5, 1, 0),
+            key(1, 0, 0, 0),
+            key(1, 5, 0, 1),
+            key(1, i64::MIN, 0, 0),
+            key(1, i64::MAX, 0, 0),
+        ];
+        let mut encoded: Vec<_> = keys.iter().map(EventKey::encode).collect();
+        keys.sort();
+        encoded.sort();
+        assert_eq!(
+            encoded,
+            keys.iter().map(EventKey::encode).collect::<Vec<_>>()
+        );
+        // window bounds hold every key at their time
+        let event = key(1, 5, agent_hash("agent"), 42);
+        assert!(EventKey::first_at(1, 5).encode() <= event.encode());
+        assert!(event.encode() <= EventKey::last_at(1, 5).encode());
+    }
+
+    #[test]
+    fn invalid_keys() {
+        let mut encoded = key(1, 0, 0, 0).encode();
+        assert!(EventKey::decode(&encoded[..22]).is_err());
+        encoded[0] = 1;
+        assert!(EventKey::decode(&encoded).is_err());
+    }
+
+    #[test]
+    fn record_hash_separates_parts() {
+        assert_ne!(record_hash(&["ab", "c"]), record_hash(&["a", "bc"]));
+        assert_eq!(record_hash(&["a", "b"]), record_hash(&["a", "b"]));
+    }
+
+    #[test]
+    fn search_keys() {
+        let (event, nanos) =
+            parse_search_key("Process Create_2023-08-06 12:34:56.000000001").unwrap();
+        assert_eq!(event, "Process Create");
+        assert_eq!(nanos, 1_691_325_296_000_000_001);
+        let (_, nanos) = parse_search_key("Process Create_2023-08-06 12:34:56.5").unwrap();
+        assert_eq!(nanos, 1_691_325_296_500_000_000);
+        assert!(parse_search_key("Process Create 2023-08-06 12:34:56").is_err());
+        assert!(parse_search_key("Process Create_2023-08-06").is_err());
+    }
+}
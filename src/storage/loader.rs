@@ -0,0 +1,480 @@
+// CSV loader of `rocks`: event{code}_logs.csv files written by `main` into the event families
+// rows are converted to typed events (structs/convert.rs) and keyed by agent, time and record hash (storage/keys.rs)
+use super::index::index_entries;
+use super::keys::EventKey;
+use super::store::{EventBatch, Put, Store};
+use crate::envs::config::RocksDbConfig;
+use crate::envs::implements::EventToCSV;
+use crate::structs::convert::{ConvertError, UTC_TIME_FORMAT};
+use crate::structs::eventTypes::*;
+use crate::structs::events::*;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use csv::{Position, ReaderBuilder, StringRecord};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// one event{code}_logs.csv written by `main`
+pub struct CsvConfig {
+    pub csv_path: String,
+    pub event_type: EventType,
+}
+
+// one variant per Sysmon event code with a typed struct in eventTypes.rs
+// `code => Variant: RawEvent => TypedEvent`
+macro_rules! event_types {
+    ($($code:literal => $name:ident: $raw:ident => $typed:ident,)*) => {
+        pub enum EventType {
+            $($name,)*
+        }
+
+        impl EventType {
+            // Sysmon event code (file name event{code}_logs.csv) to event type
+            pub fn from_code(code: &str) -> Option<EventType> {
+                match code {
+                    $($code => Some(EventType::$name),)*
+                    _ => None,
+                }
+            }
+
+            // column family, the event.action of the type
+            // (not the CSV column, older files have wrong labels e.g. event14 "Registry value set")
+            pub fn family(&self) -> &'static str {
+                match self {
+                    $(EventType::$name => $raw::EVENT_ACTION,)*
+                }
+            }
+
+            // column family name to event type
+            pub fn from_family(family: &str) -> Option<EventType> {
+                match family {
+                    $(_ if family == $raw::EVENT_ACTION => Some(EventType::$name),)*
+                    _ => None,
+                }
+            }
+
+            // type id of the keys
+            pub fn code(&self) -> u8 {
+                match self {
+                    $(EventType::$name => $raw::EVENT_CODE,)*
+                }
+            }
+
+            // header columns the CSV file must have, written by `main` for this event type
+            pub fn columns(&self) -> &'static [&'static str] {
+                match self {
+                    $(EventType::$name => $raw::FIELDS,)*
+                }
+            }
+        }
+
+        // value written by an older version, read as the typed event of the type and written again
+        // a missing or mistyped field is an error
+        pub(crate) fn typed_value(
+            event: serde_json::Value,
+            event_type: &EventType,
+        ) -> Result<serde_json::Value, Box<dyn Error>> {
+            match event_type {
+                $(EventType::$name => Ok(serde_json::to_value(serde_json::from_value::<$typed>(event)?)?),)*
+            }
+        }
+
+        fn process_record(
+            record: &csv::StringRecord,
+            headers: &csv::StringRecord,
+            event_type: &EventType,
+        ) -> Result<(u64, serde_json::Value), Box<dyn Error>> {
+            match event_type {
+                $(EventType::$name => convert::<$raw, $typed>(record, headers),)*
+            }
+        }
+    };
+}
+
+event_types! {
+    "1" => ProcessCreate: Event1 => ProcessCreateEvent,
+    "2" => FileCreateTimeChanged: Event2 => FileCreateTimeChangedEvent,
+    "3" => NetworkConnection: Event3 => NetworkConnectionEvent,
+    "5" => ProcessTerminated: Event5 => ProcessTerminatedEvent,
+    "6" => DriverLoaded: Event6 => DriverLoadedEvent,
+    "7" => ImageLoaded: Event7 => ImageLoadedEvent,
+    "8" => CreateRemoteThread: Event8 => CreateRemoteThreadEvent,
+    "9" => RawAccessRead: Event9 => RawAccessReadEvent,
+    "10" => ProcessAccessed: Event10 => ProcessAccessedEvent,
+    "11" => FileCreated: Event11 => FileCreatedEvent,
+    "12" => RegistryObjectAddedOrDeleted: Event12 => RegistryObjectAddedOrDeletedEvent,
+    "13" => RegistryValueSet: Event13 => RegistryValueSetEvent,
+    "14" => RegistryObjectRenamed: Event14 => RegistryObjectRenamedEvent,
+    "15" => FileStreamCreated: Event15 => FileStreamCreatedEvent,
+    "16" => SysmonConfigStateChanged: Event16 => SysmonConfigStateChangedEvent,
+    "17" => PipeCreated: Event17 => PipeCreatedEvent,
+    "18" => PipeConnected: Event18 => PipeConnectedEvent,
+    "19" => WmiEventFilter: Event19 => WmiEventFilterEvent,
+    "20" => WmiEventConsumer: Event20 => WmiEventConsumerEvent,
+    "21" => WmiEventConsumerToFilter: Event21 => WmiEventConsumerToFilterEvent,
+    "22" => DnsQuery: Event22 => DnsQueryEvent,
+    "23" => FileDeleteArchived: Event23 => FileDeleteArchivedEvent,
+    "24" => ClipboardChanged: Event24 => ClipboardChangedEvent,
+    "25" => ProcessTampering: Event25 => ProcessTamperingEvent,
+    "26" => FileDeleteLogged: Event26 => FileDeleteLoggedEvent,
+    "27" => FileBlockExecutable: Event27 => FileBlockExecutableEvent,
+    "28" => FileBlockShredding: Event28 => FileBlockShreddingEvent,
+    "29" => FileExecutableDetected: Event29 => FileExecutableDetectedEvent,
+}
+
+// CSV row → raw event (columns by header name) → typed event, as JSON for the value codec and the indexes
+// with the record hash of the key, taken from the raw row
+// a value that does not parse is an error, nothing is stored for it
+fn convert<R, T>(
+    record: &csv::StringRecord,
+    headers: &csv::StringRecord,
+) -> Result<(u64, serde_json::Value), Box<dyn Error>>
+where
+    R: DeserializeOwned + EventToCSV,
+    T: TryFrom<R, Error = ConvertError> + Serialize,
+{
+    let raw: R = record.deserialize(Some(headers))?;
+    let record_hash = raw.discriminator();
+    let event = T::try_from(raw)?;
+    Ok((record_hash, serde_json::to_value(&event)?))
+}
+
+// columns a file may lack, written by an older `main`
+const OPTIONAL_COLUMNS: &[&str] = &["record_id"];
+
+// Columns are found by the header row, a file without every column of its event type is refused before anything is stored
+// (written by an older or newer `main`, reading it would put values in the wrong fields)
+fn check_headers(
+    config: &CsvConfig,
+    headers: &csv::StringRecord,
+) -> Result<(usize, usize), Box<dyn Error>> {
+    let columns = config.event_type.columns();
+    let missing: Vec<&str> = columns
+        .iter()
+        .filter(|column| !headers.iter().any(|header| header == **column))
+        .copied()
+        .collect();
+    // files written before record_id was a column are still read, their keys hash every column instead
+    let (optional, missing): (Vec<&str>, Vec<&str>) = missing
+        .into_iter()
+        .partition(|column| OPTIONAL_COLUMNS.contains(column));
+    if !optional.is_empty() {
+        eprintln!(
+            "{}: no {} column, keys are made from the whole row",
+            config.csv_path,
+            optional.join(", ")
+        );
+    }
+    if !missing.is_empty() {
+        return Err(format!(
+            "{}: missing column(s) {}, the file was written by an incompatible version",
+            config.csv_path,
+            missing.join(", ")
+        )
+        .into());
+    }
+
+    let unknown: Vec<&str> = headers
+        .iter()
+        .filter(|header| !columns.contains(header))
+        .collect();
+    if !unknown.is_empty() {
+        eprintln!(
+            "{}: ignoring unknown column(s) {}",
+            config.csv_path,
+            unknown.join(", ")
+        );
+    }
+
+    let position = |name: &str| headers.iter().position(|header| header == name);
+    match (position("agent_id"), position("utc_time")) {
+        (Some(agent_column), Some(time_column)) => Ok((agent_column, time_column)),
+        _ => Err(format!("{}: missing agent_id or utc_time column", config.csv_path).into()),
+    }
+}
+
+// Rows are written in WriteBatches of `batch_size`, each batch also moves the file's offset in the db
+// an interrupted run continues after the last written batch, a later run only reads rows appended since
+pub fn process_csv(
+    config: &CsvConfig,
+    store: &Store,
+    settings: &RocksDbConfig,
+) -> Result<(), Box<dyn Error>> {
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(true)
+        .delimiter(b'\t')
+        .from_path(&config.csv_path)?;
+    let headers = rdr.headers()?.clone();
+    let (agent_column, time_column) = check_headers(config, &headers)?;
+    let cf = store.family(config.event_type.family())?;
+
+    let source = offset_source(&config.csv_path)?;
+    let file_size = fs::metadata(&config.csv_path)?.len();
+    if let Some((byte, line, record)) = store.read_offset(&source)? {
+        if byte > file_size {
+            eprintln!(
+                "{}: file is shorter than the saved offset (replaced?), reading from the start",
+                config.csv_path
+            );
+        } else {
+            let mut position = Position::new();
+            position.set_byte(byte).set_line(line).set_record(record);
+            rdr.seek(position)?;
+            if byte > 0 {
+                println!("{}: resuming at line {}", config.csv_path, line);
+            }
+        }
+    }
+
+    let mut batch = store.batch();
+
+    // malformed rows are counted and skipped, the rest of the file is still stored
+    let mut stored: usize = 0;
+    let mut unchanged: usize = 0;
+    let mut rejected: usize = 0;
+
+    // rows read since the last commit, also counts rejected and already stored rows so the offset keeps moving
+    let mut batch_rows: usize = 0;
+    let mut record = StringRecord::new();
+    while next_record(&mut rdr, &mut record, &mut rejected)? {
+        batch_rows += 1;
+        let line = record.position().map_or(0, |position| position.line());
+        match record_key(config, &record, &headers, (agent_column, time_column)) {
+            Ok((key, event)) => match batch.put(cf, &key, &event)? {
+                Put::Stored => stored += 1,
+                Put::Unchanged => unchanged += 1,
+                Put::Conflict => {
+                    rejected += 1;
+                    eprintln!(
+                        "Rejected line {}: key {} is taken by a different event",
+                        line,
+                        key.to_hex()
+                    );
+                }
+            },
+            Err(e) => {
+                rejected += 1;
+                eprintln!("Rejected line {}: {}", line, e);
+            }
+        }
+
+        if batch_rows >= settings.batch_size {
+            commit_batch(&mut batch, &source, rdr.position())?;
+            batch_rows = 0;
+            println!(
+                "{}: line {}, {:.1}% read, {} stored, {} already stored, {} rejected",
+                config.csv_path,
+                rdr.position().line(),
+                rdr.position().byte() as f64 * 100.0 / file_size.max(1) as f64,
+                stored,
+                unchanged,
+                rejected
+            );
+        }
+    }
+
+    commit_batch(&mut batch, &source, rdr.position())?;
+    println!(
+        "{}: {} records stored, {} already stored, {} rejected",
+        config.csv_path, stored, unchanged, rejected
+    );
+
+    Ok(())
+}
+
+// false at the end of the file
+// malformed rows (wrong field count, invalid UTF-8) are counted and skipped, an I/O error stops the load
+fn next_record<R: io::Read>(
+    rdr: &mut csv::Reader<R>,
+    record: &mut StringRecord,
+    rejected: &mut usize,
+) -> Result<bool, csv::Error> {
+    loop {
+        match rdr.read_record(record) {
+            Err(e) if !matches!(e.kind(), csv::ErrorKind::Io(_)) => {
+                *rejected += 1;
+                eprintln!("Rejected record: {}", e);
+            }
+            result => return result,
+        }
+    }
+}
+
+// offsets are kept by absolute path, the same file given as "./file/" and "file/" is one source
+fn offset_source(csv_path: &str) -> Result<String, Box<dyn Error>> {
+    Ok(fs::canonicalize(csv_path)?.to_string_lossy().to_string())
+}
+
+// `--bulk`: every row of the file is converted, sorted by key and ingested as SST files (see storage/bulk.rs)
+// always reads the whole file, afterwards the offset is at its end so a normal run only reads appended rows
+pub fn bulk_csv(
+    config: &CsvConfig,
+    store: &Store,
+    settings: &RocksDbConfig,
+) -> Result<(), Box<dyn Error>> {
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(true)
+        .delimiter(b'\t')
+        .from_path(&config.csv_path)?;
+    let headers = rdr.headers()?.clone();
+    let columns = check_headers(config, &headers)?;
+
+    let mut entries = Vec::new();
+    let mut rejected: usize = 0;
+    let mut record = StringRecord::new();
+    while next_record(&mut rdr, &mut record, &mut rejected)? {
+        let line = record.position().map_or(0, |position| position.line());
+        let entry = record_key(config, &record, &headers, columns).and_then(|(key, event)| {
+            let key = key.encode();
+            let value = store.codec().encode_json(&event)?;
+            Ok((key, value, index_entries(&event, &key)))
+        });
+        match entry {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                rejected += 1;
+                eprintln!("Rejected line {}: {}", line, e);
+            }
+        }
+    }
+    println!(
+        "{}: {} rows read, sorting and writing SST files",
+        config.csv_path,
+        entries.len()
+    );
+
+    let work_dir = Path::new(&format!("{}.bulk", settings.db_location))
+        .join(config.event_type.code().to_string());
+    let counts = store.bulk_load(config.event_type.family(), entries, &work_dir)?;
+    rejected += counts.conflicts;
+    if counts.conflicts > 0 {
+        eprintln!(
+            "{}: {} rows rejected, their keys are taken by different events",
+            config.csv_path, counts.conflicts
+        );
+    }
+
+    let mut batch = store.batch();
+    commit_batch(
+        &mut batch,
+        &offset_source(&config.csv_path)?,
+        rdr.position(),
+    )?;
+    println!(
+        "{}: {} records stored, {} already stored, {} rejected",
+        config.csv_path, counts.stored, counts.unchanged, rejected
+    );
+
+    Ok(())
+}
+
+// the batch and the position of the next unread row in one write
+fn commit_batch(
+    batch: &mut EventBatch,
+    source: &str,
+    position: &Position,
+) -> Result<(), rocksdb::Error> {
+    batch.set_offset(
+        source,
+        (position.byte(), position.line(), position.record()),
+    );
+    batch.commit()
+}
+
+// key and typed event of one CSV row
+fn record_key(
+    config: &CsvConfig,
+    record: &StringRecord,
+    headers: &StringRecord,
+    (agent_column, time_column): (usize, usize),
+) -> Result<(EventKey, serde_json::Value), Box<dyn Error>> {
+    let (record_hash, event) = process_record(record, headers, &config.event_type)?;
+    let naive_dt =
+        NaiveDateTime::parse_from_str(record.get(time_column).unwrap_or_default(), UTC_TIME_FORMAT)
+            .map_err(|e| format!("error parsing datetime: {}", e))?;
+    let utc_time = Utc.from_utc_datetime(&naive_dt);
+    let key = EventKey::new(
+        config.event_type.code(),
+        &utc_time,
+        record.get(agent_column).unwrap_or_default(),
+        record_hash,
+    )
+    .ok_or("timestamp is out of range for nanosecond precision")?;
+    Ok((key, event))
+}
+
+// every event{code}_logs.csv in csv_location, sorted by event code
+// files of codes without a typed struct (e.g. event4) are reported and skipped
+pub fn find_csv_files(csv_location: &str) -> Result<Vec<CsvConfig>, Box<dyn Error>> {
+    let mut found = Vec::new();
+    for entry in fs::read_dir(csv_location)? {
+        let file_name = entry?.file_name().to_string_lossy().to_string();
+        let Some(code) = file_name
+            .strip_prefix("event")
+            .and_then(|rest| rest.strip_suffix("_logs.csv"))
+        else {
+            continue;
+        };
+        match (code.parse::<u32>(), EventType::from_code(code)) {
+            (Ok(number), Some(event_type)) => found.push((
+                number,
+                CsvConfig {
+                    csv_path: Path::new(csv_location)
+                        .join(&file_name)
+                        .to_string_lossy()
+                        .to_string(),
+                    event_type,
+                },
+            )),
+            _ => eprintln!("Skipping {}: unknown event type number", file_name),
+        }
+    }
+    found.sort_by_key(|(number, _)| *number);
+    Ok(found.into_iter().map(|(_, config)| config).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // fails every read, like a file on a disk that went away
+    struct BrokenReader;
+
+    impl io::Read for BrokenReader {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk gone"))
+        }
+    }
+
+    fn reader<R: io::Read>(source: R) -> csv::Reader<R> {
+        ReaderBuilder::new()
+            .has_headers(true)
+            .delimiter(b'\t')
+            .from_reader(source)
+    }
+
+    #[test]
+    fn malformed_rows_are_skipped() {
+        let mut rdr = reader(&b"a\tb\n1\t2\n3\n\xff\t4\n5\t6\n"[..]);
+        let mut record = StringRecord::new();
+        let mut rejected = 0;
+        let mut rows = Vec::new();
+        while next_record(&mut rdr, &mut record, &mut rejected).unwrap() {
+            rows.push(record.as_slice().to_string());
+        }
+        assert_eq!(rows, ["12", "56"]);
+        assert_eq!(rejected, 2);
+    }
+
+    #[test]
+    fn io_errors_stop_the_load() {
+        let mut rdr = reader(BrokenReader);
+        let mut record = StringRecord::new();
+        let mut rejected = 0;
+        assert!(next_record(&mut rdr, &mut record, &mut rejected).is_err());
+        assert_eq!(rejected, 0);
+    }
+}
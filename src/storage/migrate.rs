@@ -0,0 +1,215 @@
+// `migrate`: rewrite the values of one event family with the codec of the store and write their index entries
+// JSON values (and values of the other codec) are decoded and encoded again, keys are not changed
+// Process Create / Image loaded values with the Hashes string get it split by algorithm (indexed as hashes.*)
+// running it again only rewrites what is left, index entries are written again (same keys)
+//
+// databases of the first `rocks` (JSON values under "{event.action}_{epoch nanos}" keys in the default family)
+// are moved into the event families first, see `migrate_legacy`
+use super::families::scan_options;
+use super::index::index_entries;
+use super::keys::{record_hash, EventKey, KEY_LEN};
+use super::loader::{typed_value, EventType};
+use super::store::{family, Store};
+use super::values::decode_value;
+use crate::structs::eventTypes::Hashes;
+use chrono::{DateTime, Utc};
+use rocksdb::IteratorMode;
+use std::error::Error;
+
+// values rewritten per transaction
+const BATCH_SIZE: usize = 10_000;
+// event codes whose typed struct splits Hashes by algorithm, older values have the Sysmon string
+const SPLIT_HASHES: [u8; 2] = [1, 7];
+
+#[derive(Default)]
+pub struct MigrateCounts {
+    pub rewritten: usize,
+    pub unchanged: usize,
+    pub indexed: usize,
+    pub failed: usize,
+}
+
+// Sysmon Hashes string to `Hashes`, true when the event was changed
+fn split_hashes(event: &mut serde_json::Value) -> Result<bool, Box<dyn Error>> {
+    let Some(text) = event["hashes"].as_str() else {
+        return Ok(false);
+    };
+    let hashes = match text {
+        "" => Hashes::default(),
+        text => text.parse::<Hashes>()?,
+    };
+    event["hashes"] = serde_json::to_value(hashes)?;
+    Ok(true)
+}
+
+// values that do not decode are reported on stderr, counted as failed and left as they are
+pub fn migrate_family(
+    store: &Store,
+    type_id: u8,
+    name: &str,
+) -> Result<MigrateCounts, Box<dyn Error>> {
+    let db = store.db();
+    let codec = store.codec();
+    let cf = family(db, name)?;
+    let mut counts = MigrateCounts::default();
+
+    let mut transaction = db.transaction();
+    let mut pending: usize = 0;
+    for item in db.iterator_cf_opt(cf, scan_options(), IteratorMode::Start) {
+        let (key, value) = item?;
+        let decoded = decode_value(&value).and_then(|mut event| {
+            let primary_key: [u8; KEY_LEN] = key.as_ref().try_into()?;
+            let split = SPLIT_HASHES.contains(&type_id) && split_hashes(&mut event)?;
+            Ok((event, primary_key, split))
+        });
+        let (event, primary_key, split) = match decoded {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                counts.failed += 1;
+                eprintln!("{}: key {:02x?}: {}", name, key, e);
+                continue;
+            }
+        };
+        for (family_name, index_key) in index_entries(&event, &primary_key) {
+            transaction.put_cf(family(db, family_name)?, index_key, [])?;
+        }
+        counts.indexed += 1;
+        if codec.wrote(&value) && !split {
+            counts.unchanged += 1;
+        } else {
+            transaction.put_cf(cf, &key, codec.encode_json(&event)?)?;
+            counts.rewritten += 1;
+        }
+        pending += 1;
+        if pending == BATCH_SIZE {
+            transaction.commit()?;
+            transaction = db.transaction();
+            pending = 0;
+        }
+    }
+    transaction.commit()?;
+    Ok(counts)
+}
+
+// "Process Create_1691334001311000000" → event type, None for every other key of the default family (loader offsets)
+fn legacy_key(key: &[u8]) -> Option<EventType> {
+    let key = std::str::from_utf8(key).ok()?;
+    let (event_action, nanos) = key.rsplit_once('_')?;
+    nanos.parse::<i64>().ok()?;
+    EventType::from_family(event_action)
+}
+
+// legacy row → (binary key, value of the current typed event)
+// the old rows have no RecordID, the old string key is the record hash (unique in the old db)
+fn legacy_event(
+    key: &[u8],
+    value: &[u8],
+    event_type: &EventType,
+) -> Result<(EventKey, serde_json::Value), Box<dyn Error>> {
+    let mut event = decode_value(value)?;
+    if SPLIT_HASHES.contains(&event_type.code()) {
+        split_hashes(&mut event)?;
+    }
+    let event = typed_value(event, event_type)?;
+    let utc_time: DateTime<Utc> = event["utc_time"]
+        .as_str()
+        .ok_or("missing utc_time")?
+        .parse()?;
+    let agent_id = event["agent_id"].as_str().unwrap_or_default();
+    let record_hash = record_hash(&[&String::from_utf8_lossy(key)]);
+    let event_key = EventKey::new(event_type.code(), &utc_time, agent_id, record_hash)
+        .ok_or("timestamp is out of range for nanosecond precision")?;
+    Ok((event_key, event))
+}
+
+// Move the rows of a pre-series database into the event families: binary key, store codec, index entries
+// each row is deleted in the transaction that writes it, running it again finds nothing left
+// rows that do not read as their typed event are reported on stderr, counted as failed and left as they are
+pub fn migrate_legacy(store: &Store) -> Result<MigrateCounts, Box<dyn Error>> {
+    let db = store.db();
+    let codec = store.codec();
+    let mut counts = MigrateCounts::default();
+
+    let mut transaction = db.transaction();
+    let mut pending: usize = 0;
+    for item in db.iterator(IteratorMode::Start) {
+        let (key, value) = item?;
+        let Some(event_type) = legacy_key(&key) else {
+            continue;
+        };
+        let (event_key, event) = match legacy_event(&key, &value, &event_type) {
+            Ok(moved) => moved,
+            Err(e) => {
+                counts.failed += 1;
+                eprintln!("{}: {}", String::from_utf8_lossy(&key), e);
+                continue;
+            }
+        };
+        let primary_key = event_key.encode();
+        transaction.put_cf(
+            family(db, event_type.family())?,
+            primary_key,
+            codec.encode_json(&event)?,
+        )?;
+        for (family_name, index_key) in index_entries(&event, &primary_key) {
+            transaction.put_cf(family(db, family_name)?, index_key, [])?;
+        }
+        transaction.delete(&key)?;
+        counts.rewritten += 1;
+        counts.indexed += 1;
+        pending += 1;
+        if pending == BATCH_SIZE {
+            transaction.commit()?;
+            transaction = db.transaction();
+            pending = 0;
+        }
+    }
+    transaction.commit()?;
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Process Create row of the first `rocks` (serde_json of the old struct, Hashes as one string)
+    const LEGACY_KEY: &[u8] = b"Process Create_1691334001311000001";
+    const LEGACY_VALUE: &str = r#"{"agent_name":"DESKTOP-1","agent_id":"7c8c1b8e","event_action":"Process Create","utc_time":"2023-08-06T15:00:01.311Z","process_guid":"{d9d3b9b4-b5b1-64cf-8e05-000000000e00}","process_id":6348,"image":"C:\\Windows\\System32\\cmd.exe","file_version":"10.0.19041.1","description":"Windows Command Processor","product":"Microsoft Windows","company":"Microsoft Corporation","original_file_name":"Cmd.Exe","command_line":"cmd.exe","current_directory":"C:\\","user":"DESKTOP-1\\user","logon_guid":"{d9d3b9b4-b0b2-64cf-d1a0-020000000000}","logon_id":0,"terminal_session_id":1,"integrity_level":"High","hashes":"SHA256=B99D114B267FFD068C3289199B6DF95A9F9E64872D6C2A666BC7FDE7B2C1B6E1","parent_process_guid":"{d9d3b9b4-b5a0-64cf-7c05-000000000e00}","parent_process_id":1200,"parent_image":"C:\\Windows\\explorer.exe","parent_command_line":"explorer.exe","parent_user":"DESKTOP-1\\user"}"#;
+
+    #[test]
+    fn legacy_keys() {
+        assert!(matches!(
+            legacy_key(LEGACY_KEY),
+            Some(EventType::ProcessCreate)
+        ));
+        assert!(legacy_key(b"offset//data/event1_logs.csv").is_none());
+        assert!(legacy_key(b"Unknown event_1691334001311000001").is_none());
+        assert!(legacy_key(b"Process Create_x").is_none());
+    }
+
+    #[test]
+    fn legacy_row_is_rekeyed_and_typed() {
+        let (key, event) = legacy_event(
+            LEGACY_KEY,
+            LEGACY_VALUE.as_bytes(),
+            &EventType::ProcessCreate,
+        )
+        .unwrap();
+        assert_eq!(key.type_id, 1);
+        assert_eq!(key.nanos, 1_691_334_001_311_000_000);
+        assert_eq!(
+            key.record_hash,
+            record_hash(&["Process Create_1691334001311000001"])
+        );
+        assert_eq!(
+            event["hashes"]["sha256"],
+            "B99D114B267FFD068C3289199B6DF95A9F9E64872D6C2A666BC7FDE7B2C1B6E1"
+        );
+        assert!(event["hashes"]["md5"].is_null());
+    }
+
+    #[test]
+    fn legacy_row_of_another_shape_fails() {
+        assert!(legacy_event(LEGACY_KEY, br#"{"image":"a"}"#, &EventType::ProcessCreate).is_err());
+    }
+}
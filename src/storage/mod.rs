@@ -0,0 +1,11 @@
+pub mod bulk;
+pub mod families;
+pub mod index;
+pub mod keys;
+pub mod loader;
+pub mod migrate;
+pub mod output;
+pub mod pagination;
+pub mod query;
+pub mod store;
+pub mod values;
@@ -0,0 +1,272 @@
+// Page output of `select` / `nano-select`, `--format json|ndjson|csv|table` (json by default)
+//
+// json   : one document {"edges": [{"cursor", "node"}, ...], "page_info": {...}}
+// ndjson : one {"cursor", "node"} per line, then {"page_info": {...}}
+// csv    : cursor and node fields (nested ones as "hashes.sha256"), page info on stderr
+// table  : node fields in aligned columns, page info below
+use super::pagination::{encode_cursor, Page};
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error;
+use std::io::Write;
+use std::str::FromStr;
+
+// longest table cell, command lines and paths are cut
+const TABLE_CELL_MAX: usize = 40;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Ndjson,
+    Csv,
+    Table,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            "csv" => Ok(OutputFormat::Csv),
+            "table" => Ok(OutputFormat::Table),
+            _ => Err(format!(
+                "unknown format {:?}, expected json, ndjson, csv or table",
+                s
+            )),
+        }
+    }
+}
+
+// takes `--format <format>` / `--format=<format>` out of the arguments, positional ones keep their index
+pub fn take_format(args: Vec<String>) -> Result<(OutputFormat, Vec<String>), Box<dyn Error>> {
+    let mut format = OutputFormat::default();
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--format" {
+            format = args.next().ok_or("--format requires a value")?.parse()?;
+        } else if let Some(value) = arg.strip_prefix("--format=") {
+            format = value.parse()?;
+        } else {
+            rest.push(arg);
+        }
+    }
+    Ok((format, rest))
+}
+
+#[derive(Serialize)]
+pub struct PageInfo {
+    // matching events of the whole window, null unless asked for (`--total-count`)
+    pub total_count: Option<usize>,
+    // events of the page
+    pub count: usize,
+    pub start_cursor: Option<String>,
+    pub end_cursor: Option<String>,
+    pub has_previous_page: bool,
+    pub has_next_page: bool,
+}
+
+#[derive(Serialize)]
+struct Edge<'a> {
+    cursor: String,
+    node: &'a Value,
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    edges: Vec<Edge<'a>>,
+    page_info: &'a PageInfo,
+}
+
+#[derive(Serialize)]
+struct Trailer<'a> {
+    page_info: &'a PageInfo,
+}
+
+impl Page {
+    pub fn page_info(&self, total_count: Option<usize>) -> PageInfo {
+        PageInfo {
+            total_count,
+            count: self.events.len(),
+            start_cursor: self.start_cursor(),
+            end_cursor: self.end_cursor(),
+            has_previous_page: self.has_previous_page,
+            has_next_page: self.has_next_page,
+        }
+    }
+
+    fn edges(&self) -> Vec<Edge<'_>> {
+        self.events
+            .iter()
+            .map(|(key, node)| Edge {
+                cursor: encode_cursor(key),
+                node,
+            })
+            .collect()
+    }
+}
+
+// "name" → text of the field, nested objects as "parent.name", null is empty
+fn flatten(prefix: &str, value: &Value, cells: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(fields) => {
+            for (name, value) in fields {
+                let name = match prefix {
+                    "" => name.clone(),
+                    _ => format!("{}.{}", prefix, name),
+                };
+                flatten(&name, value, cells);
+            }
+        }
+        Value::Null => cells.push((prefix.to_string(), String::new())),
+        Value::String(text) => cells.push((prefix.to_string(), text.clone())),
+        other => cells.push((prefix.to_string(), other.to_string())),
+    }
+}
+
+// columns in first seen order, one row of cells per event
+fn columns(page: &Page) -> (Vec<String>, Vec<Vec<String>>) {
+    let flat: Vec<Vec<(String, String)>> = page
+        .events
+        .iter()
+        .map(|(_, node)| {
+            let mut cells = Vec::new();
+            flatten("", node, &mut cells);
+            cells
+        })
+        .collect();
+    let mut columns: Vec<String> = Vec::new();
+    for (name, _) in flat.iter().flatten() {
+        if !columns.contains(name) {
+            columns.push(name.clone());
+        }
+    }
+    let rows = flat
+        .into_iter()
+        .map(|cells| {
+            columns
+                .iter()
+                .map(|column| {
+                    cells
+                        .iter()
+                        .find(|(name, _)| name == column)
+                        .map(|(_, text)| text.clone())
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .collect();
+    (columns, rows)
+}
+
+fn cell(text: &str) -> String {
+    let text = text.replace(['\r', '\n', '\t'], " ");
+    if text.chars().count() <= TABLE_CELL_MAX {
+        return text;
+    }
+    let cut: String = text.chars().take(TABLE_CELL_MAX - 3).collect();
+    format!("{}...", cut)
+}
+
+fn write_table(out: &mut dyn Write, page: &Page, info: &PageInfo) -> Result<(), Box<dyn Error>> {
+    let (columns, rows) = columns(page);
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(|text| cell(text)).collect())
+        .collect();
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([column.chars().count()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(text, width)| format!("{:<width$}", text, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    if !columns.is_empty() {
+        writeln!(out, "{}", line(&columns))?;
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        writeln!(out, "{}", line(&rule))?;
+        for row in &rows {
+            writeln!(out, "{}", line(row))?;
+        }
+        writeln!(out)?;
+    }
+    let total = match info.total_count {
+        Some(total_count) => format!(" of {}", total_count),
+        None => String::new(),
+    };
+    writeln!(
+        out,
+        "{}{} events, has_previous_page: {}, has_next_page: {}",
+        info.count, total, info.has_previous_page, info.has_next_page
+    )?;
+    writeln!(
+        out,
+        "start_cursor: {}\nend_cursor: {}",
+        info.start_cursor.as_deref().unwrap_or(""),
+        info.end_cursor.as_deref().unwrap_or("")
+    )?;
+    Ok(())
+}
+
+pub fn write_page(
+    out: &mut dyn Write,
+    format: OutputFormat,
+    page: &Page,
+    total_count: Option<usize>,
+) -> Result<(), Box<dyn Error>> {
+    let info = page.page_info(total_count);
+    match format {
+        OutputFormat::Json => {
+            let envelope = Envelope {
+                edges: page.edges(),
+                page_info: &info,
+            };
+            serde_json::to_writer(&mut *out, &envelope)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Ndjson => {
+            for edge in page.edges() {
+                serde_json::to_writer(&mut *out, &edge)?;
+                writeln!(out)?;
+            }
+            serde_json::to_writer(&mut *out, &Trailer { page_info: &info })?;
+            writeln!(out)?;
+        }
+        OutputFormat::Csv => {
+            let (columns, rows) = columns(page);
+            let mut writer = csv::Writer::from_writer(&mut *out);
+            writer.write_record(
+                ["cursor"]
+                    .into_iter()
+                    .chain(columns.iter().map(String::as_str)),
+            )?;
+            for ((key, _), row) in page.events.iter().zip(&rows) {
+                writer.write_record([encode_cursor(key)].iter().chain(row))?;
+            }
+            writer.flush()?;
+            // stdout stays one csv document
+            eprintln!("{}", serde_json::to_string(&Trailer { page_info: &info })?);
+        }
+        OutputFormat::Table => write_table(out, page, &info)?,
+    }
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,299 @@
+// Relay cursor pagination of a `Query`, for `select`, `nano-select` and `server` (GET /select, /graphql)
+//
+// cursors are opaque: url-safe base64 of the primary key of an event, so they stay valid while the event is stored
+// and a search resumes exactly at it, even among events of the same nanosecond
+// first / after : the first `count` matching events after the cursor (or from the start of the window)
+// last / before : the last `count` matching events before the cursor (or up to the end of the window)
+// events of a page are oldest first either way
+//
+// one event is read past the page for the flag of the search side, one event at the cursor for the other side
+use super::keys::{EventKey, KEY_LEN};
+use super::query::Query;
+use super::values::decode_event;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use rocksdb::{Direction, DB};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde_json::{json, Value};
+use std::error::Error;
+
+pub fn encode_cursor(key: &EventKey) -> String {
+    URL_SAFE_NO_PAD.encode(key.encode())
+}
+
+pub fn decode_cursor(cursor: &str) -> Result<EventKey, Box<dyn Error>> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(cursor)
+        .map_err(|e| format!("invalid cursor {:?}: {}", cursor, e))?;
+    if bytes.len() != KEY_LEN {
+        return Err(format!("invalid cursor {:?}", cursor).into());
+    }
+    EventKey::decode(&bytes)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Pagination {
+    First {
+        count: usize,
+        after: Option<EventKey>,
+    },
+    Last {
+        count: usize,
+        before: Option<EventKey>,
+    },
+}
+
+// "" is no cursor (the Node resolvers send it)
+fn cursor_arg(cursor: Option<&str>) -> Result<Option<EventKey>, Box<dyn Error>> {
+    cursor
+        .filter(|cursor| !cursor.is_empty())
+        .map(decode_cursor)
+        .transpose()
+}
+
+impl Pagination {
+    // Relay arguments, first/after and last/before cannot be mixed
+    // no `first` / `last`: `default_count` events, none of the four: the first events of the window
+    pub fn new(
+        first: Option<usize>,
+        after: Option<&str>,
+        last: Option<usize>,
+        before: Option<&str>,
+        default_count: usize,
+    ) -> Result<Pagination, Box<dyn Error>> {
+        let after = cursor_arg(after)?;
+        let before = cursor_arg(before)?;
+        let forward = first.is_some() || after.is_some();
+        let backward = last.is_some() || before.is_some();
+        if forward && backward {
+            return Err("first / after and last / before cannot be used together".into());
+        }
+        Ok(if backward {
+            Pagination::Last {
+                count: last.unwrap_or(default_count),
+                before,
+            }
+        } else {
+            Pagination::First {
+                count: first.unwrap_or(default_count),
+                after,
+            }
+        })
+    }
+
+    // `nano-select` / GET /select arguments: "last" is last/before, anything else first/after
+    pub fn from_direction(
+        direction: Option<&str>,
+        count: usize,
+        cursor: Option<&str>,
+    ) -> Result<Pagination, Box<dyn Error>> {
+        let cursor = cursor_arg(cursor)?;
+        Ok(match direction {
+            Some("last") => Pagination::Last {
+                count,
+                before: cursor,
+            },
+            _ => Pagination::First {
+                count,
+                after: cursor,
+            },
+        })
+    }
+
+    // skip `offset` events from the start of the search (end of the window for last), the cursor is not used
+    pub fn skip(self, db: &DB, query: &Query, offset: usize) -> Result<Pagination, Box<dyn Error>> {
+        if offset == 0 {
+            return Ok(self);
+        }
+        Ok(match self {
+            Pagination::First { count, .. } => {
+                let skipped = query.page_as::<IgnoredAny>(
+                    db,
+                    &Pagination::First {
+                        count: offset,
+                        after: None,
+                    },
+                )?;
+                Pagination::First {
+                    count,
+                    after: skipped.events.last().map(|(key, _)| *key),
+                }
+            }
+            Pagination::Last { count, .. } => {
+                let skipped = query.page_as::<IgnoredAny>(
+                    db,
+                    &Pagination::Last {
+                        count: offset,
+                        before: None,
+                    },
+                )?;
+                Pagination::Last {
+                    count,
+                    before: skipped.events.first().map(|(key, _)| *key),
+                }
+            }
+        })
+    }
+}
+
+// One page of a query, oldest event first
+// nodes are JSON for the binaries, the typed event struct for GraphQL (`Query::page_as`)
+pub struct Page<T = Value> {
+    pub events: Vec<(EventKey, T)>,
+    pub has_previous_page: bool,
+    pub has_next_page: bool,
+}
+
+impl<T> Page<T> {
+    pub fn start_cursor(&self) -> Option<String> {
+        self.events.first().map(|(key, _)| encode_cursor(key))
+    }
+
+    pub fn end_cursor(&self) -> Option<String> {
+        self.events.last().map(|(key, _)| encode_cursor(key))
+    }
+}
+
+impl Page {
+    // GET /select response, one {"cursor", "node"} per event then the page info (binaries: storage/output.rs)
+    // total_count: matching events of the whole window (`Query::count`), null when not asked for
+    pub fn to_json(&self, total_count: Option<usize>) -> Value {
+        let mut items: Vec<Value> = self
+            .events
+            .iter()
+            .map(|(key, node)| json!({"cursor": encode_cursor(key), "node": node}))
+            .collect();
+        items.push(json!({
+            "total_count": total_count,
+            "counts": self.events.len(),
+            "start_cursor": self.start_cursor().unwrap_or_default(),
+            "end_cursor": self.end_cursor().unwrap_or_default(),
+            "has_previous_page": self.has_previous_page,
+            "has_next_page": self.has_next_page,
+        }));
+        Value::Array(items)
+    }
+}
+
+impl Query {
+    pub fn page(&self, db: &DB, pagination: &Pagination) -> Result<Page, Box<dyn Error>> {
+        self.page_as(db, pagination)
+    }
+
+    // only the events of the page are decoded, into `T`
+    // a read error or an event that cannot be decoded fails the page, it never comes back short
+    pub fn page_as<T: DeserializeOwned>(
+        &self,
+        db: &DB,
+        pagination: &Pagination,
+    ) -> Result<Page<T>, Box<dyn Error>> {
+        let (count, cursor, direction, back) = match *pagination {
+            Pagination::First { count, after } => {
+                (count, after, Direction::Forward, Direction::Reverse)
+            }
+            Pagination::Last { count, before } => {
+                (count, before, Direction::Reverse, Direction::Forward)
+            }
+        };
+        if cursor.is_some_and(|cursor| cursor.type_id != self.type_id()) {
+            return Err(format!("cursor of another event type than {}", self.family()).into());
+        }
+
+        let mut events = Vec::new();
+        let mut more = false;
+        for row in self.rows_from(db, cursor.as_ref(), direction)? {
+            let (key, value) = row?;
+            let key = EventKey::decode(&key)?;
+            // the cursor event itself was on the previous page
+            if cursor == Some(key) {
+                continue;
+            }
+            if events.len() == count {
+                more = true;
+                break;
+            }
+            let event = decode_event(&value)
+                .map_err(|e| format!("Error reading {}: {}", encode_cursor(&key), e))?;
+            events.push((key, event));
+        }
+
+        // the cursor event, or a matching one behind it when it is gone
+        let behind = match cursor {
+            Some(cursor) => self
+                .rows_from(db, Some(&cursor), back)?
+                .next()
+                .transpose()?
+                .is_some(),
+            None => false,
+        };
+
+        if let Direction::Reverse = direction {
+            events.reverse();
+        }
+        let (has_previous_page, has_next_page) = match direction {
+            Direction::Forward => (behind, more),
+            Direction::Reverse => (more, behind),
+        };
+        Ok(Page {
+            events,
+            has_previous_page,
+            has_next_page,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::keys::agent_hash;
+
+    #[test]
+    fn cursors_round_trip() {
+        let key = EventKey {
+            type_id: 3,
+            nanos: -42,
+            agent_hash: agent_hash("agent"),
+            record_hash: u64::MAX,
+        };
+        let cursor = encode_cursor(&key);
+        assert!(cursor
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(decode_cursor(&cursor).unwrap(), key);
+    }
+
+    #[test]
+    fn invalid_cursors() {
+        assert!(decode_cursor("not base64!").is_err());
+        // valid base64 of a shorter key
+        assert!(decode_cursor(&URL_SAFE_NO_PAD.encode([2u8; 22])).is_err());
+        // version 1 key
+        assert!(decode_cursor(&URL_SAFE_NO_PAD.encode([1u8; KEY_LEN])).is_err());
+    }
+
+    #[test]
+    fn relay_arguments() {
+        let cursor = encode_cursor(&EventKey::first_at(1, 0));
+        assert!(matches!(
+            Pagination::new(None, None, None, None, 10).unwrap(),
+            Pagination::First {
+                count: 10,
+                after: None
+            }
+        ));
+        assert!(matches!(
+            Pagination::new(None, None, Some(5), Some(&cursor), 10).unwrap(),
+            Pagination::Last {
+                count: 5,
+                before: Some(_)
+            }
+        ));
+        assert!(matches!(
+            Pagination::new(Some(5), Some(""), None, None, 10).unwrap(),
+            Pagination::First {
+                count: 5,
+                after: None
+            }
+        ));
+        assert!(Pagination::new(Some(5), None, None, Some(&cursor), 10).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+// Reader side: one search of `select` / `nano-select` on a db opened with `open_families`
+// an event type, a time window (inclusive) and filters (storage/index.rs), pages in storage/pagination.rs
+use super::index::{count, scan, Filters, Rows};
+use super::keys::{parse_search_key, EventKey, KEY_LEN};
+use crate::structs::events::EVENT_TYPES;
+use rocksdb::{ColumnFamily, Direction, DB};
+use serde::Deserialize;
+use std::error::Error;
+
+pub struct Query {
+    // column family, the event.action of the type
+    family: &'static str,
+    type_id: u8,
+    lower: [u8; KEY_LEN],
+    upper: [u8; KEY_LEN],
+    filters: Filters,
+}
+
+// Filter arguments of `nano-select` (positional) and `server` (query string) by name, missing or "" is no filter
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct FilterArgs {
+    // image path contains
+    pub image: Option<String>,
+    pub process_id: Option<String>,
+    pub process_guid: Option<String>,
+    pub parent_process_guid: Option<String>,
+    pub agent_id: Option<String>,
+    pub user: Option<String>,
+    // network and dns events: ip or CIDR block, port or "low-high", host / query name (any case)
+    pub destination_ip: Option<String>,
+    pub destination_port: Option<String>,
+    pub destination_hostname: Option<String>,
+    pub query_name: Option<String>,
+    // process create / image loaded: "SHA256=...", "IMPHASH=..."
+    pub hash: Option<String>,
+}
+
+impl FilterArgs {
+    pub fn filters(&self) -> Result<Filters, Box<dyn Error>> {
+        let process_id = self
+            .process_id
+            .as_deref()
+            .filter(|pid| !pid.is_empty())
+            .map(|pid| {
+                pid.trim()
+                    .parse::<u32>()
+                    .map_err(|e| format!("process_id: invalid value {}: {}", pid, e))
+            })
+            .transpose()?
+            .map(|pid| pid.to_string());
+        Filters::default()
+            .image_contains(self.image.as_deref())
+            .exact("process_id", process_id.as_deref())
+            .exact("process_guid", self.process_guid.as_deref())
+            .exact("parent_process_guid", self.parent_process_guid.as_deref())
+            .exact("agent_id", self.agent_id.as_deref())
+            .exact("user", self.user.as_deref())
+            .ip_range("destination_ip", self.destination_ip.as_deref())?
+            .port_range("destination_port", self.destination_port.as_deref())?
+            .exact("destination_hostname", self.destination_hostname.as_deref())
+            .exact("query_name", self.query_name.as_deref())
+            .hash(self.hash.as_deref())
+    }
+}
+
+impl Query {
+    // event.action and epoch nanoseconds of the first and last event of the window
+    pub fn new(event_action: &str, start: i64, end: i64) -> Result<Query, Box<dyn Error>> {
+        let (type_id, family) = EVENT_TYPES
+            .iter()
+            .find(|(_, name)| *name == event_action)
+            .copied()
+            .ok_or_else(|| format!("Unknown event type: {}", event_action))?;
+        Ok(Query {
+            family,
+            type_id,
+            lower: EventKey::first_at(type_id, start).encode(),
+            upper: EventKey::last_at(type_id, end).encode(),
+            filters: Filters::default(),
+        })
+    }
+
+    // "event name_datetime" arguments of the command line, the event name of `end_key` is not used
+    pub fn from_search_keys(start_key: &str, end_key: &str) -> Result<Query, Box<dyn Error>> {
+        let (event_name, start) = parse_search_key(start_key)?;
+        let (_, end) = parse_search_key(end_key)?;
+        Query::new(&event_name, start, end)
+    }
+
+    pub fn filter(mut self, filters: Filters) -> Query {
+        self.filters = filters;
+        self
+    }
+
+    pub fn family(&self) -> &'static str {
+        self.family
+    }
+
+    // event code of the type, first byte after the version in the keys
+    pub fn type_id(&self) -> u8 {
+        self.type_id
+    }
+
+    fn cf<'a>(&self, db: &'a DB) -> Result<&'a ColumnFamily, Box<dyn Error>> {
+        db.cf_handle(self.family)
+            .ok_or_else(|| format!("Unknown event type: {}", self.family).into())
+    }
+
+    // matching events of the window, oldest first
+    pub fn rows<'a>(&'a self, db: &'a DB) -> Result<Rows<'a>, Box<dyn Error>> {
+        self.rows_from(db, None, Direction::Forward)
+    }
+
+    // matching events from `from` (included, None: the start of the search) to the edge of the window
+    // a cursor outside the window starts at its edge, events outside it are never returned
+    pub fn rows_from<'a>(
+        &'a self,
+        db: &'a DB,
+        from: Option<&EventKey>,
+        direction: Direction,
+    ) -> Result<Rows<'a>, Box<dyn Error>> {
+        let from = from.map(|key| key.encode());
+        let (lower, upper) = match (&from, direction) {
+            (Some(from), Direction::Forward) => (from.max(&self.lower), &self.upper),
+            (Some(from), Direction::Reverse) => (&self.lower, from.min(&self.upper)),
+            (None, _) => (&self.lower, &self.upper),
+        };
+        scan(db, self.cf(db)?, &self.filters, lower, upper, direction)
+    }
+
+    // matching events of the window, a full pass over them (or their index keys): only when asked for
+    pub fn count(&self, db: &DB) -> Result<usize, Box<dyn Error>> {
+        count(db, self.cf(db)?, &self.filters, &self.lower, &self.upper)
+    }
+}
@@ -0,0 +1,233 @@
+use super::bulk::{bulk_load, BulkCounts, BulkEntry};
+use super::families::family_options;
+use super::index::{index_entries, INDEXES};
+use super::keys::{EventKey, KEY_LEN};
+use super::values::{same_event, ValueCodec};
+use crate::envs::implements::EventToCSV;
+use crate::structs::convert::{ConvertError, TypedEvent};
+use crate::structs::events::EVENT_TYPES;
+use rocksdb::{
+    ColumnFamily, ColumnFamilyDescriptor, OptimisticTransactionDB, Options, SingleThreaded,
+    WriteBatchWithTransaction,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+pub type EventDb = OptimisticTransactionDB<SingleThreaded>;
+
+// Writer side of the database, used by the loaders (`main`, `rocks`, `migrate`)
+// events are written with `codec`, readers use `Query` (storage/query.rs)
+pub struct Store {
+    db: EventDb,
+    codec: ValueCodec,
+}
+
+impl Store {
+    pub fn open(db_path: &str, codec: ValueCodec) -> Result<Store, rocksdb::Error> {
+        Ok(Store {
+            db: open_db(db_path)?,
+            codec,
+        })
+    }
+
+    pub fn db(&self) -> &EventDb {
+        &self.db
+    }
+
+    pub fn codec(&self) -> ValueCodec {
+        self.codec
+    }
+
+    pub fn family(&self, name: &str) -> Result<&ColumnFamily, Box<dyn Error>> {
+        family(&self.db, name)
+    }
+
+    pub fn batch(&self) -> EventBatch<'_> {
+        EventBatch::new(&self.db, self.codec)
+    }
+
+    // offset saved by `EventBatch::set_offset`, None when the source was never loaded
+    pub fn read_offset(&self, source: &str) -> Result<Option<Offset>, Box<dyn Error>> {
+        let Some(value) = self.db.get(format!("{}{}", OFFSET_PREFIX, source))? else {
+            return Ok(None);
+        };
+        if value.len() != 24 {
+            return Err(format!("invalid offset of {}", source).into());
+        }
+        let number =
+            |at: usize| u64::from_be_bytes(value[at..at + 8].try_into().unwrap_or_default());
+        Ok(Some((number(0), number(8), number(16))))
+    }
+
+    // see storage/bulk.rs, entries are encoded with the codec of the store
+    pub fn bulk_load(
+        &self,
+        family_name: &str,
+        entries: Vec<BulkEntry>,
+        work_dir: &Path,
+    ) -> Result<BulkCounts, Box<dyn Error>> {
+        bulk_load(
+            &self.db,
+            self.family(family_name)?,
+            family_name,
+            entries,
+            work_dir,
+        )
+    }
+
+    // Parse one search response page into raw events, convert them to typed events and write them in one batch
+    // like `rocks`, events that do not convert are skipped, returned with the reason for the caller to report
+    pub fn store_page<R, T>(&self, data: &serde_json::Value) -> Result<PageCounts, Box<dyn Error>>
+    where
+        R: EventToCSV,
+        T: TryFrom<R, Error = ConvertError> + TypedEvent + Serialize,
+    {
+        let cf = self.family(R::EVENT_ACTION)?;
+        let mut batch = self.batch();
+        let mut counts = PageCounts::default();
+
+        for raw in R::parse(data) {
+            let record_hash = raw.discriminator();
+            let event = match T::try_from(raw) {
+                Ok(event) => event,
+                Err(e) => {
+                    counts.rejected.push(e.to_string());
+                    continue;
+                }
+            };
+            let Some(key) = EventKey::new(
+                R::EVENT_CODE,
+                event.utc_time(),
+                event.agent_id(),
+                record_hash,
+            ) else {
+                counts
+                    .rejected
+                    .push("timestamp is out of range for nanosecond precision".to_string());
+                continue;
+            };
+            match batch.put(cf, &key, &serde_json::to_value(&event)?)? {
+                Put::Stored => counts.stored += 1,
+                Put::Unchanged => counts.unchanged += 1,
+                Put::Conflict => counts.rejected.push(format!(
+                    "key {} is taken by a different event",
+                    key.to_hex()
+                )),
+            }
+        }
+
+        batch.commit()?;
+        Ok(counts)
+    }
+}
+
+// Open for writing, the column family of every event type and index is created when missing
+fn open_db(db_path: &str) -> Result<EventDb, rocksdb::Error> {
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let families = EVENT_TYPES
+        .iter()
+        .map(|(_, name)| *name)
+        .chain(INDEXES.iter().map(|index| index.family))
+        .map(|name| ColumnFamilyDescriptor::new(name, family_options(name)));
+    OptimisticTransactionDB::open_cf_descriptors(&opts, db_path, families)
+}
+
+// column family of one event type or index, every family is opened by `open_db`
+pub(crate) fn family<'a>(
+    db: &'a EventDb,
+    event_action: &str,
+) -> Result<&'a ColumnFamily, Box<dyn Error>> {
+    db.cf_handle(event_action)
+        .ok_or_else(|| format!("Column family {} is not open", event_action).into())
+}
+
+// result of `Store::store_page`, rejected events by reason
+#[derive(Default)]
+pub struct PageCounts {
+    pub stored: usize,
+    pub unchanged: usize,
+    pub rejected: Vec<String>,
+}
+
+pub enum Put {
+    Stored,
+    // same key and same event (in any value format), the event was loaded before
+    Unchanged,
+    // same key, different value, the stored event is kept
+    Conflict,
+}
+
+// prefix of loader offsets in the default column family, "offset/{source}" → byte, line, record (u64 big-endian)
+const OFFSET_PREFIX: &str = "offset/";
+
+// byte, line and record number of the next unread row
+pub type Offset = (u64, u64, u64);
+
+// Puts collected in a WriteBatch and written at once by `commit`, memory is bounded by the batch size
+// an event is never silently overwritten: keys already in the db or in the batch are compared, not replaced
+// index entries of a stored event go into the same batch
+pub struct EventBatch<'a> {
+    db: &'a EventDb,
+    codec: ValueCodec,
+    batch: WriteBatchWithTransaction<true>,
+    pending: HashMap<[u8; KEY_LEN], Vec<u8>>,
+}
+
+impl<'a> EventBatch<'a> {
+    pub(crate) fn new(db: &'a EventDb, codec: ValueCodec) -> EventBatch<'a> {
+        EventBatch {
+            db,
+            codec,
+            batch: WriteBatchWithTransaction::default(),
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn put(
+        &mut self,
+        cf: &ColumnFamily,
+        key: &EventKey,
+        event: &serde_json::Value,
+    ) -> Result<Put, Box<dyn Error>> {
+        let value = self.codec.encode_json(event)?;
+        let key = key.encode();
+        let stored = match self.pending.get(&key) {
+            Some(pending) => Some(pending.clone()),
+            None => self.db.get_cf(cf, key)?,
+        };
+        match stored {
+            Some(stored) if same_event(&stored, &value) => Ok(Put::Unchanged),
+            Some(_) => Ok(Put::Conflict),
+            None => {
+                self.batch.put_cf(cf, key, &value);
+                for (family_name, index_key) in index_entries(event, &key) {
+                    self.batch
+                        .put_cf(family(self.db, family_name)?, index_key, []);
+                }
+                self.pending.insert(key, value);
+                Ok(Put::Stored)
+            }
+        }
+    }
+
+    // written with the events of the batch, a crash never leaves the offset ahead of the stored events
+    pub fn set_offset(&mut self, source: &str, offset: Offset) {
+        let (byte, line, record) = offset;
+        let mut value = Vec::with_capacity(24);
+        value.extend_from_slice(&byte.to_be_bytes());
+        value.extend_from_slice(&line.to_be_bytes());
+        value.extend_from_slice(&record.to_be_bytes());
+        self.batch
+            .put(format!("{}{}", OFFSET_PREFIX, source), value);
+    }
+
+    pub fn commit(&mut self) -> Result<(), rocksdb::Error> {
+        self.db.write(std::mem::take(&mut self.batch))?;
+        self.pending.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+
+// RocksDB values: one format byte, then the typed event encoded with the codec
+//
+// | format | event                         |
+// | 0x01   | MessagePack, fields by name   |
+// | 0x02   | CBOR, fields by name          |
+// | '{'    | JSON text (written before the format byte, no header)
+//
+// both codecs keep field names, readers decode straight into the typed struct of the event type (`decode_event`),
+// or into the few fields a filter reads (storage/index.rs), no serde_json::Value tree per scanned row
+// values are written from the serde_json::Value the indexes are read from, fields in name order
+const MESSAGE_PACK: u8 = 0x01;
+const CBOR: u8 = 0x02;
+const LEGACY_JSON: u8 = b'{';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueCodec {
+    MessagePack,
+    Cbor,
+}
+
+impl ValueCodec {
+    // `rocksdb.value_codec` setting
+    pub fn from_name(name: &str) -> Option<ValueCodec> {
+        match name {
+            "msgpack" => Some(ValueCodec::MessagePack),
+            "cbor" => Some(ValueCodec::Cbor),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ValueCodec::MessagePack => "msgpack",
+            ValueCodec::Cbor => "cbor",
+        }
+    }
+
+    fn format(self) -> u8 {
+        match self {
+            ValueCodec::MessagePack => MESSAGE_PACK,
+            ValueCodec::Cbor => CBOR,
+        }
+    }
+
+    // through serde_json::Value, fields read back as JSON writes them (ip addresses as text, not octets)
+    pub fn encode<T: Serialize>(self, event: &T) -> Result<Vec<u8>, Box<dyn Error>> {
+        self.encode_json(&serde_json::to_value(event)?)
+    }
+
+    pub fn encode_json(self, event: &serde_json::Value) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut value = vec![self.format()];
+        match self {
+            ValueCodec::MessagePack => rmp_serde::encode::write_named(&mut value, event)?,
+            ValueCodec::Cbor => ciborium::ser::into_writer(event, &mut value)?,
+        }
+        Ok(value)
+    }
+
+    // true when the value is already written with this codec (nothing to migrate)
+    pub fn wrote(self, value: &[u8]) -> bool {
+        value.first() == Some(&self.format())
+    }
+}
+
+// any struct of the event's fields (`ProcessCreateEvent`, ...), or serde_json::Value when the type is not known
+pub fn decode_event<T: DeserializeOwned>(value: &[u8]) -> Result<T, Box<dyn Error>> {
+    match value.first() {
+        Some(&MESSAGE_PACK) => Ok(rmp_serde::from_slice(&value[1..])?),
+        Some(&CBOR) => Ok(ciborium::de::from_reader(&value[1..])?),
+        Some(&LEGACY_JSON) => Ok(serde_json::from_slice(value)?),
+        Some(format) => Err(format!("unknown value format {:#04x}", format).into()),
+        None => Err("empty value".into()),
+    }
+}
+
+pub fn decode_value(value: &[u8]) -> Result<serde_json::Value, Box<dyn Error>> {
+    decode_event(value)
+}
+
+// a stored value is the event of a new one, also when `rocksdb.value_codec` changed since it was written
+// (or it has no format byte), reloading the same events stays idempotent; an unreadable stored value never is
+pub fn same_event(stored: &[u8], value: &[u8]) -> bool {
+    if stored == value {
+        return true;
+    }
+    if stored.first() == value.first() {
+        return false;
+    }
+    match (decode_value(stored), decode_value(value)) {
+        (Ok(stored), Ok(value)) => stored == value,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::eventTypes::NetworkConnectionEvent;
+    use serde_json::json;
+    use std::net::IpAddr;
+
+    fn network_event() -> serde_json::Value {
+        json!({
+            "agent_name": "DESKTOP-1",
+            "agent_id": "7c8c1b8e",
+            "event_action": "Network connection detected",
+            "utc_time": "2023-08-06T15:00:01.311Z",
+            "process_guid": "{d9d3b9b4-b5b1-64cf-8e05-000000000e00}",
+            "process_id": 6348,
+            "image": "C:\\Windows\\System32\\svchost.exe",
+            "user": "NT AUTHORITY\\SYSTEM",
+            "protocol": "tcp",
+            "initiated": true,
+            "source_is_ipv6": false,
+            "source_ip": "10.0.0.5",
+            "source_hostname": "",
+            "source_port": 50123,
+            "source_port_name": "",
+            "destination_is_ipv6": true,
+            "destination_ip": "fe80::1",
+            "destination_hostname": "",
+            "destination_port": 443,
+            "destination_port_name": "https",
+        })
+    }
+
+    #[test]
+    fn typed_decode_of_every_codec() {
+        for codec in [ValueCodec::MessagePack, ValueCodec::Cbor] {
+            let value = codec.encode_json(&network_event()).unwrap();
+            assert!(codec.wrote(&value));
+            let event: NetworkConnectionEvent = decode_event(&value).unwrap();
+            assert_eq!(event.source_ip, "10.0.0.5".parse::<IpAddr>().unwrap());
+            assert_eq!(event.destination_ip, "fe80::1".parse::<IpAddr>().unwrap());
+            assert_eq!(event.destination_port, 443);
+            assert_eq!(decode_value(&value).unwrap(), network_event());
+        }
+    }
+
+    #[test]
+    fn typed_encode_keeps_ip_text() {
+        let event: NetworkConnectionEvent = serde_json::from_value(network_event()).unwrap();
+        let value = ValueCodec::MessagePack.encode(&event).unwrap();
+        assert_eq!(decode_value(&value).unwrap()["destination_ip"], "fe80::1");
+    }
+
+    #[test]
+    fn legacy_json_and_unknown_formats() {
+        let legacy = serde_json::to_vec(&network_event()).unwrap();
+        let event: NetworkConnectionEvent = decode_event(&legacy).unwrap();
+        assert_eq!(event.process_id, 6348);
+        assert!(decode_value(&[0x7f, 0x00]).is_err());
+        assert!(decode_value(&[]).is_err());
+    }
+
+    #[test]
+    fn events_written_by_another_codec_are_the_same() {
+        // loaded with cbor, reloaded with msgpack
+        let stored = ValueCodec::Cbor.encode_json(&network_event()).unwrap();
+        let value = ValueCodec::MessagePack
+            .encode_json(&network_event())
+            .unwrap();
+        assert!(same_event(&stored, &value));
+        assert!(same_event(&value, &value));
+        let legacy = serde_json::to_vec(&network_event()).unwrap();
+        assert!(same_event(&legacy, &value));
+
+        let mut other = network_event();
+        other["destination_port"] = json!(80);
+        let other = ValueCodec::MessagePack.encode_json(&other).unwrap();
+        assert!(!same_event(&stored, &other));
+        assert!(!same_event(&value, &other));
+        assert!(!same_event(&[0x7f], &value));
+    }
+}
@@ -0,0 +1,558 @@
+// Raw CSV rows (`structs::events`, every column Option<String>) to typed RocksDB values (`structs::eventTypes`)
+// a value that does not parse is an error, never a zero or UNSPECIFIED default
+use crate::structs::eventTypes::*;
+use crate::structs::events::*;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// Sysmon "UtcTime" format, e.g. 2023-08-06 12:34:56.789
+pub const UTC_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+// Field that could not be converted, with the raw value as it was in the CSV
+#[derive(Debug)]
+pub struct ConvertError {
+    pub field: &'static str,
+    pub value: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "field `{}`: invalid value {:?}, {}",
+            self.field, self.value, self.reason
+        )
+    }
+}
+
+impl Error for ConvertError {}
+
+fn invalid(field: &'static str, value: &str, reason: impl fmt::Display) -> ConvertError {
+    ConvertError {
+        field,
+        value: value.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+// strings are kept as they are, Sysmon leaves some of them out (e.g. ParentUser on old versions)
+fn text(_field: &'static str, value: Option<String>) -> Result<String, ConvertError> {
+    Ok(value.unwrap_or_default())
+}
+
+// numbers, "true"/"false" and ip addresses must be present and valid
+fn parse<T>(field: &'static str, value: Option<String>) -> Result<T, ConvertError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let value = value.ok_or_else(|| invalid(field, "", "value is missing"))?;
+    value.parse::<T>().map_err(|e| invalid(field, &value, e))
+}
+
+// LogonId is a 64 bit LUID written in hex, e.g. 0x3e7
+fn hex(field: &'static str, value: Option<String>) -> Result<u64, ConvertError> {
+    let value = value.ok_or_else(|| invalid(field, "", "value is missing"))?;
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .ok_or_else(|| invalid(field, &value, "expected 0x prefix"))?;
+    u64::from_str_radix(digits, 16).map_err(|e| invalid(field, &value, e))
+}
+
+fn time(field: &'static str, value: Option<String>) -> Result<DateTime<Utc>, ConvertError> {
+    let value = value.ok_or_else(|| invalid(field, "", "value is missing"))?;
+    let naive_dt = NaiveDateTime::parse_from_str(&value, UTC_TIME_FORMAT)
+        .map_err(|e| invalid(field, &value, e))?;
+    Ok(Utc.from_utc_datetime(&naive_dt))
+}
+
+// Hashes column, "" when the Sysmon config computes no hash
+fn hashes(field: &'static str, value: Option<String>) -> Result<Hashes, ConvertError> {
+    match value.as_deref() {
+        None | Some("") => Ok(Hashes::default()),
+        Some(value) => value.parse().map_err(|e| invalid(field, value, e)),
+    }
+}
+
+// "SHA1=...,MD5=...,SHA256=...,IMPHASH=...", any order and case, every algorithm at most once
+impl FromStr for Hashes {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut hashes = Hashes::default();
+        for entry in value.split(',') {
+            let (algorithm, digest) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected ALGORITHM=digest, got {:?}", entry))?;
+            let (slot, digest_len) = match algorithm.trim().to_ascii_uppercase().as_str() {
+                "SHA1" => (&mut hashes.sha1, 40),
+                "MD5" => (&mut hashes.md5, 32),
+                "SHA256" => (&mut hashes.sha256, 64),
+                "IMPHASH" => (&mut hashes.imphash, 32),
+                _ => return Err(format!("unknown hash algorithm {:?}", algorithm)),
+            };
+            let digest = digest.trim();
+            if digest.len() != digest_len || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("{} is not {} hex digits", algorithm, digest_len));
+            }
+            if slot.is_some() {
+                return Err(format!("{} given twice", algorithm));
+            }
+            *slot = Some(digest.to_ascii_uppercase());
+        }
+        Ok(hashes)
+    }
+}
+
+// what the storage key of a typed event is built from
+pub trait TypedEvent {
+    fn agent_id(&self) -> &str;
+    fn utc_time(&self) -> &DateTime<Utc>;
+}
+
+// `TryFrom<EventN>` and `TypedEvent` for every typed event, `field: conversion` per line
+// `from raw_field` when the typed field is named differently from the CSV column
+macro_rules! convert_event {
+    ($($raw:ident => $typed:ident { $($field:ident: $conv:ident $(from $source:ident)?),* $(,)? })*) => {
+        $(
+            impl TryFrom<$raw> for $typed {
+                type Error = ConvertError;
+
+                fn try_from(raw: $raw) -> Result<Self, Self::Error> {
+                    Ok($typed {
+                        agent_name: raw.agent_name.unwrap_or_default(),
+                        agent_id: raw.agent_id.unwrap_or_default(),
+                        event_action: raw.event_action.unwrap_or_default(),
+                        $($field: $conv(stringify!($field), convert_event!(@source raw $field $($source)?))?,)*
+                    })
+                }
+            }
+
+            impl TypedEvent for $typed {
+                fn agent_id(&self) -> &str {
+                    &self.agent_id
+                }
+
+                fn utc_time(&self) -> &DateTime<Utc> {
+                    &self.utc_time
+                }
+            }
+        )*
+    };
+    (@source $raw:ident $field:ident) => {
+        $raw.$field
+    };
+    (@source $raw:ident $field:ident $source:ident) => {
+        $raw.$source
+    };
+}
+
+convert_event! {
+    // EVENT 1
+    Event1 => ProcessCreateEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        image: text,
+        file_version: text,
+        description: text,
+        product: text,
+        company: text,
+        original_file_name: text,
+        command_line: text,
+        current_directory: text,
+        user: text,
+        logon_guid: text,
+        logon_id: hex,
+        terminal_session_id: parse,
+        integrity_level: text,
+        hashes: hashes,
+        parent_process_guid: text,
+        parent_process_id: parse,
+        parent_image: text,
+        parent_command_line: text,
+        parent_user: text,
+    }
+
+    // EVENT 2
+    Event2 => FileCreateTimeChangedEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        image: text,
+        target_filename: text,
+        creation_utc_time: time,
+        previous_creation_utc_time: time,
+        user: text,
+    }
+
+    // EVENT 3
+    Event3 => NetworkConnectionEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        image: text,
+        user: text,
+        protocol: text,
+        initiated: parse,
+        source_is_ipv6: parse,
+        source_ip: parse,
+        source_hostname: text,
+        source_port: parse,
+        source_port_name: text,
+        destination_is_ipv6: parse,
+        destination_ip: parse,
+        destination_hostname: text,
+        destination_port: parse,
+        destination_port_name: text,
+    }
+
+    // EVENT 5
+    Event5 => ProcessTerminatedEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        image: text,
+        user: text,
+    }
+
+    // EVENT 6
+    Event6 => DriverLoadedEvent {
+        utc_time: time,
+        image_loaded: text,
+        hashes: text,
+        signed: parse,
+        signature: text,
+        signature_status: text,
+    }
+
+    // EVENT 7
+    Event7 => ImageLoadedEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        image: text,
+        image_loaded: text,
+        file_version: text,
+        description: text,
+        product: text,
+        company: text,
+        original_file_name: text,
+        hashes: hashes,
+        signed: parse,
+        signature: text,
+        signature_status: text,
+        user: text,
+    }
+
+    // EVENT 8
+    Event8 => CreateRemoteThreadEvent {
+        utc_time: time,
+        source_process_guid: text,
+        source_process_id: parse,
+        source_image: text,
+        target_process_guid: text,
+        target_process_id: parse,
+        target_image: text,
+        new_thread_id: parse,
+        start_address: text,
+        start_module: text,
+        start_function: text,
+        source_user: text,
+        target_user: text,
+    }
+
+    // EVENT 9
+    Event9 => RawAccessReadEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        image: text,
+        device: text,
+        user: text,
+    }
+
+    // EVENT 10
+    Event10 => ProcessAccessedEvent {
+        utc_time: time,
+        source_process_guid: text,
+        source_process_id: parse,
+        source_thread_id: parse,
+        source_image: text,
+        target_process_guid: text,
+        target_process_id: parse,
+        target_thread_id: parse,
+        target_image: text,
+        granted_access: text,
+        call_trace: text,
+        source_user: text,
+        target_user: text,
+    }
+
+    // EVENT 11
+    Event11 => FileCreatedEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        image: text,
+        target_filename: text,
+        creation_utc_time: time,
+        user: text,
+    }
+
+    // EVENT 12
+    Event12 => RegistryObjectAddedOrDeletedEvent {
+        event_type: text,
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        image: text,
+        target_object: text,
+        user: text,
+    }
+
+    // EVENT 13
+    Event13 => RegistryValueSetEvent {
+        event_type: text,
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        image: text,
+        target_object: text,
+        details: text,
+        user: text,
+    }
+
+    // EVENT 14
+    Event14 => RegistryObjectRenamedEvent {
+        event_type: text,
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        image: text,
+        target_object: text,
+        new_name: text,
+        user: text,
+    }
+
+    // EVENT 15
+    Event15 => FileStreamCreatedEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        image: text,
+        target_filename: text,
+        creation_utc_time: time,
+        hash: text,
+        contents: text,
+        user: text,
+    }
+
+    // EVENT 16
+    Event16 => SysmonConfigStateChangedEvent {
+        utc_time: time,
+        configuration: text,
+        configuration_file_hash: text,
+    }
+
+    // EVENT 17
+    Event17 => PipeCreatedEvent {
+        event_type: text,
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        pipe_name: text,
+        image: text,
+        user: text,
+    }
+
+    // EVENT 18
+    Event18 => PipeConnectedEvent {
+        event_type: text,
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        pipe_name: text,
+        image: text,
+        user: text,
+    }
+
+    // EVENT 19
+    Event19 => WmiEventFilterEvent {
+        utc_time: time,
+        event_type: text,
+        operation: text,
+        user: text,
+        event_namespace: text,
+        name: text,
+        query: text,
+    }
+
+    // EVENT 20
+    Event20 => WmiEventConsumerEvent {
+        utc_time: time,
+        event_type: text,
+        operation: text,
+        user: text,
+        name: text,
+        consumer_type: text from types,
+        destination: text,
+    }
+
+    // EVENT 21
+    Event21 => WmiEventConsumerToFilterEvent {
+        utc_time: time,
+        event_type: text,
+        operation: text,
+        user: text,
+        consumer: text,
+        filter: text,
+    }
+
+    // EVENT 22
+    Event22 => DnsQueryEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        query_name: text,
+        query_status: parse,
+        query_results: text,
+        image: text,
+        user: text,
+    }
+
+    // EVENT 23
+    Event23 => FileDeleteArchivedEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        user: text,
+        image: text,
+        target_filename: text,
+        hashes: text,
+        is_executable: parse,
+        archived: parse,
+    }
+
+    // EVENT 24
+    Event24 => ClipboardChangedEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        image: text,
+        session: parse,
+        client_info: text,
+        hashes: text,
+        archived: parse,
+        user: text,
+    }
+
+    // EVENT 25
+    Event25 => ProcessTamperingEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        image: text,
+        type_description: text from types,
+        user: text,
+    }
+
+    // EVENT 26
+    Event26 => FileDeleteLoggedEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        user: text,
+        image: text,
+        target_filename: text,
+        hashes: text,
+        is_executable: parse,
+    }
+
+    // EVENT 27
+    Event27 => FileBlockExecutableEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        user: text,
+        image: text,
+        target_filename: text,
+        hashes: text,
+    }
+
+    // EVENT 28
+    Event28 => FileBlockShreddingEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        user: text,
+        image: text,
+        target_filename: text,
+        hashes: text,
+        is_executable: parse,
+    }
+
+    // EVENT 29
+    Event29 => FileExecutableDetectedEvent {
+        utc_time: time,
+        process_guid: text,
+        process_id: parse,
+        user: text,
+        image: text,
+        target_filename: text,
+        hashes: text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA1: &str = "a9993e364706816aba3e25717850c26c9cd0d89d";
+    const MD5: &str = "900150983cd24fb0d6963f7d28e17f72";
+    const SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn hashes_any_order_and_case() {
+        let hashes: Hashes = format!("sha256={}, MD5={},SHA1={}", SHA256, MD5, SHA1)
+            .parse()
+            .unwrap();
+        assert_eq!(
+            hashes.sha1.as_deref(),
+            Some(SHA1.to_ascii_uppercase().as_str())
+        );
+        assert_eq!(
+            hashes.md5.as_deref(),
+            Some(MD5.to_ascii_uppercase().as_str())
+        );
+        assert_eq!(
+            hashes.sha256.as_deref(),
+            Some(SHA256.to_ascii_uppercase().as_str())
+        );
+        assert_eq!(hashes.imphash, None);
+    }
+
+    #[test]
+    fn invalid_hashes() {
+        for value in [
+            SHA256.to_string(),
+            format!("SHA512={}", SHA256),
+            format!("SHA256={}", SHA1),
+            format!("MD5={}", "z".repeat(32)),
+            format!("MD5={},MD5={}", MD5, MD5),
+        ] {
+            assert!(value.parse::<Hashes>().is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn empty_hashes_column() {
+        let hashes = super::hashes("hashes", Some(String::new())).unwrap();
+        assert!(hashes.sha256.is_none() && hashes.imphash.is_none());
+        assert!(super::hashes("hashes", Some("MD5=0".to_string())).is_err());
+    }
+}
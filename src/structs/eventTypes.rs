@@ -1,16 +1,53 @@
-#![allow(dead_code)]
-
 extern crate chrono;
-use chrono::{DateTime, Utc};
+use async_graphql::{ComplexObject, SimpleObject};
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
 use std::net::IpAddr;
 
+// ip addresses are text in every value format, the binary codecs would write octets (storage/values.rs)
+mod ip_text {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::net::IpAddr;
+
+    pub fn serialize<S: Serializer>(ip: &IpAddr, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(ip)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<IpAddr, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+// times are RFC 3339 strings in GraphQL like in typeDefs.js, not a DateTime scalar (same text as the JSON values)
+fn rfc3339(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+// Sysmon "Hashes" ("SHA1=...,MD5=...,SHA256=...,IMPHASH=...") split by algorithm, uppercase hex
+// an algorithm the Sysmon config does not compute is None
+#[derive(Serialize, Deserialize, Debug, Default, SimpleObject)]
+pub struct Hashes {
+    pub sha1: Option<String>,
+    pub md5: Option<String>,
+    pub sha256: Option<String>,
+    pub imphash: Option<String>,
+}
+
 // EVENT 1
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "ProcessCreateEve", rename_fields = "snake_case")]
 pub struct ProcessCreateEvent {
     pub agent_name: String,
     pub agent_id: String,
     pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
     pub utc_time: DateTime<Utc>,
     pub process_guid: String,
     pub process_id: u32,
@@ -24,10 +61,10 @@ pub struct ProcessCreateEvent {
     pub current_directory: String,
     pub user: String,
     pub logon_guid: String,
-    pub logon_id: u32,
+    pub logon_id: u64,
     pub terminal_session_id: u32,
     pub integrity_level: String,
-    pub hashes: String,
+    pub hashes: Hashes,
     pub parent_process_guid: String,
     pub parent_process_id: u32,
     pub parent_image: String,
@@ -36,26 +73,59 @@ pub struct ProcessCreateEvent {
 }
 
 // EVENT 2
-struct FileCreateTimeChangedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    image: String,
-    target_filename: String,
-    creation_utc_time: DateTime<Utc>,
-    previous_creation_utc_time: DateTime<Utc>,
-    user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "FileCreateTimeChangedEve", rename_fields = "snake_case")]
+pub struct FileCreateTimeChangedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub image: String,
+    pub target_filename: String,
+    #[graphql(
+        skip,
+        name = "creation_utc_time",
+        derived(
+            name = "creation_utc_time_text",
+            into = "String",
+            with = "rfc3339",
+            owned
+        )
+    )]
+    pub creation_utc_time: DateTime<Utc>,
+    #[graphql(
+        skip,
+        name = "previous_creation_utc_time",
+        derived(
+            name = "previous_creation_utc_time_text",
+            into = "String",
+            with = "rfc3339",
+            owned
+        )
+    )]
+    pub previous_creation_utc_time: DateTime<Utc>,
+    pub user: String,
 }
 
 // EVENT 3
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "NetworkConnectionEve", rename_fields = "snake_case", complex)]
 pub struct NetworkConnectionEvent {
     pub agent_name: String,
     pub agent_id: String,
     pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
     pub utc_time: DateTime<Utc>,
     pub process_guid: String,
     pub process_id: u32,
@@ -63,18 +133,37 @@ pub struct NetworkConnectionEvent {
     pub user: String,
     pub protocol: String,
     pub initiated: bool,
+    // snake_case renaming would make it "..._ipv_6"
+    #[graphql(name = "source_is_ipv6")]
     pub source_is_ipv6: bool,
+    #[graphql(skip)]
+    #[serde(with = "ip_text")]
     pub source_ip: IpAddr,
     pub source_hostname: String,
     pub source_port: u16,
     pub source_port_name: String,
+    #[graphql(name = "destination_is_ipv6")]
     pub destination_is_ipv6: bool,
+    #[graphql(skip)]
+    #[serde(with = "ip_text")]
     pub destination_ip: IpAddr,
     pub destination_hostname: String,
     pub destination_port: u16,
     pub destination_port_name: String,
 }
 
+// no GraphQL ip scalar, addresses are text like in typeDefs.js
+#[ComplexObject(rename_fields = "snake_case")]
+impl NetworkConnectionEvent {
+    async fn source_ip(&self) -> String {
+        self.source_ip.to_string()
+    }
+
+    async fn destination_ip(&self) -> String {
+        self.destination_ip.to_string()
+    }
+}
+
 // EVENT 4 : sysmon event 불필요
 // struct SysmonServiceStateChangeEvent {
 //     utc_time: DateTime<Utc>,
@@ -84,139 +173,212 @@ pub struct NetworkConnectionEvent {
 // }
 
 // EVENT 5
-struct ProcessTerminatedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    image: String,
-    user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "ProcessTerminatedEve", rename_fields = "snake_case")]
+pub struct ProcessTerminatedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub image: String,
+    pub user: String,
 }
 
 // EVENT 6
-struct DriverLoadedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    image_loaded: String,
-    hashes: String,
-    signed: bool,
-    signature: String,
-    signature_status: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "DriverLoadedEve", rename_fields = "snake_case")]
+pub struct DriverLoadedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub image_loaded: String,
+    pub hashes: String,
+    pub signed: bool,
+    pub signature: String,
+    pub signature_status: String,
 }
 
 // EVENT 7
-struct ImageLoadedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    image: String,
-    image_loaded: String,
-    file_version: String,
-    description: String,
-    product: String,
-    company: String,
-    original_file_name: String,
-    hashes: String,
-    signed: bool,
-    signature: String,
-    signature_status: String,
-    user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "ImageLoadedEve", rename_fields = "snake_case")]
+pub struct ImageLoadedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub image: String,
+    pub image_loaded: String,
+    pub file_version: String,
+    pub description: String,
+    pub product: String,
+    pub company: String,
+    pub original_file_name: String,
+    pub hashes: Hashes,
+    pub signed: bool,
+    pub signature: String,
+    pub signature_status: String,
+    pub user: String,
 }
 
 // EVENT 8
-struct CreateRemoteThreadEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    source_process_guid: String,
-    source_process_id: u32,
-    source_image: String,
-    target_process_guid: String,
-    target_process_id: u32,
-    target_image: String,
-    new_thread_id: u32,
-    start_address: String,
-    start_module: String,
-    start_function: String,
-    source_user: String,
-    target_user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "CreateRemoteThreadEve", rename_fields = "snake_case")]
+pub struct CreateRemoteThreadEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub source_process_guid: String,
+    pub source_process_id: u32,
+    pub source_image: String,
+    pub target_process_guid: String,
+    pub target_process_id: u32,
+    pub target_image: String,
+    pub new_thread_id: u32,
+    pub start_address: String,
+    pub start_module: String,
+    pub start_function: String,
+    pub source_user: String,
+    pub target_user: String,
 }
 
 // EVENT 9
-struct RawAccessReadEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    image: String,
-    device: String,
-    user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "RawAccessReadEve", rename_fields = "snake_case")]
+pub struct RawAccessReadEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub image: String,
+    pub device: String,
+    pub user: String,
 }
 
 // EVENT 10
-struct ProcessAccessedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    source_process_guid: String,
-    source_process_id: u32,
-    source_thread_id: u32,
-    source_image: String,
-    target_process_guid: String,
-    target_process_id: u32,
-    target_image: String,
-    granted_access: String,
-    call_trace: String,
-    source_user: String,
-    target_user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "ProcessAccessedEve", rename_fields = "snake_case")]
+pub struct ProcessAccessedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub source_process_guid: String,
+    pub source_process_id: u32,
+    pub source_thread_id: u32,
+    pub source_image: String,
+    pub target_process_guid: String,
+    pub target_process_id: u32,
+    pub target_thread_id: u32,
+    pub target_image: String,
+    pub granted_access: String,
+    pub call_trace: String,
+    pub source_user: String,
+    pub target_user: String,
 }
 
 // EVENT 11
-struct FileCreatedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    image: String,
-    target_filename: String,
-    creation_utc_time: DateTime<Utc>,
-    user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "FileCreatedEve", rename_fields = "snake_case")]
+pub struct FileCreatedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub image: String,
+    pub target_filename: String,
+    #[graphql(
+        skip,
+        name = "creation_utc_time",
+        derived(
+            name = "creation_utc_time_text",
+            into = "String",
+            with = "rfc3339",
+            owned
+        )
+    )]
+    pub creation_utc_time: DateTime<Utc>,
+    pub user: String,
 }
 
 // EVENT 12
-struct RegistryObjectAddedOrDeletedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    event_type: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    image: String,
-    target_object: String,
-    user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "RegistryObjectAddedOrDeletedEve", rename_fields = "snake_case")]
+pub struct RegistryObjectAddedOrDeletedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    pub event_type: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub image: String,
+    pub target_object: String,
+    pub user: String,
 }
 
 // EVENT 13
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "RegValueSetEve", rename_fields = "snake_case")]
 pub struct RegistryValueSetEvent {
     pub agent_name: String,
     pub agent_id: String,
     pub event_action: String,
     pub event_type: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
     pub utc_time: DateTime<Utc>,
     pub process_guid: String,
     pub process_id: u32,
@@ -227,144 +389,351 @@ pub struct RegistryValueSetEvent {
 }
 
 // EVENT 14
-struct RegistryObjectRenamedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    event_type: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    image: String,
-    target_object: String,
-    new_name: String,
-    user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "RegistryObjectRenamedEve", rename_fields = "snake_case")]
+pub struct RegistryObjectRenamedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    pub event_type: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub image: String,
+    pub target_object: String,
+    pub new_name: String,
+    pub user: String,
 }
 
 // EVENT 15
-struct FileStreamCreatedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    image: String,
-    target_filename: String,
-    creation_utc_time: DateTime<Utc>,
-    hash: String,
-    contents: String,
-    user: String,
-}
-
-// EVENT 16 : sysmon event 불필요
-// struct SysmonConfigStateChangedEvent {
-//     utc_time: DateTime<Utc>,
-//     configuration: String,
-//     configuration_file_hash: String,
-// }
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "FileStreamCreatedEve", rename_fields = "snake_case")]
+pub struct FileStreamCreatedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub image: String,
+    pub target_filename: String,
+    #[graphql(
+        skip,
+        name = "creation_utc_time",
+        derived(
+            name = "creation_utc_time_text",
+            into = "String",
+            with = "rfc3339",
+            owned
+        )
+    )]
+    pub creation_utc_time: DateTime<Utc>,
+    pub hash: String,
+    pub contents: String,
+    pub user: String,
+}
+
+// EVENT 16
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "SysmonConfigStateChangedEve", rename_fields = "snake_case")]
+pub struct SysmonConfigStateChangedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub configuration: String,
+    pub configuration_file_hash: String,
+}
 
 // EVENT 17
-struct PipeCreatedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    event_type: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    pipe_name: String,
-    image: String,
-    user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "PipeCreatedEve", rename_fields = "snake_case")]
+pub struct PipeCreatedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    pub event_type: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub pipe_name: String,
+    pub image: String,
+    pub user: String,
 }
 
 // EVENT 18
-struct PipeConnectedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    event_type: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    pipe_name: String,
-    image: String,
-    user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "PipeConnectedEve", rename_fields = "snake_case")]
+pub struct PipeConnectedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    pub event_type: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub pipe_name: String,
+    pub image: String,
+    pub user: String,
+}
+
+// EVENT 19
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "WmiEventFilterEve", rename_fields = "snake_case")]
+pub struct WmiEventFilterEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub event_type: String,
+    pub operation: String,
+    pub user: String,
+    pub event_namespace: String,
+    pub name: String,
+    pub query: String,
+}
+
+// EVENT 20
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "WmiEventConsumerEve", rename_fields = "snake_case")]
+pub struct WmiEventConsumerEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub event_type: String,
+    pub operation: String,
+    pub user: String,
+    pub name: String,
+    pub consumer_type: String,
+    pub destination: String,
 }
 
-// Event 19, 20, 21 이벤트 미생성
+// EVENT 21
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "WmiEventConsumerToFilterEve", rename_fields = "snake_case")]
+pub struct WmiEventConsumerToFilterEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub event_type: String,
+    pub operation: String,
+    pub user: String,
+    pub consumer: String,
+    pub filter: String,
+}
 
 // EVENT 22
-struct DnsQueryEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    query_name: String,
-    query_status: u32,
-    query_results: String,
-    image: String,
-    user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "DnsQueryEve", rename_fields = "snake_case")]
+pub struct DnsQueryEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub query_name: String,
+    pub query_status: u32,
+    pub query_results: String,
+    pub image: String,
+    pub user: String,
 }
 
 // EVENT 23
-struct FileDeleteArchivedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    user: String,
-    image: String,
-    target_filename: String,
-    hashes: String,
-    is_executable: bool,
-    archived: bool,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "FileDeleteArchivedEve", rename_fields = "snake_case")]
+pub struct FileDeleteArchivedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub user: String,
+    pub image: String,
+    pub target_filename: String,
+    pub hashes: String,
+    pub is_executable: bool,
+    pub archived: bool,
 }
 
 // EVENT 24
-struct ClipboardChangedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    image: String,
-    session: u32,
-    client_info: String,
-    hashes: String,
-    archived: bool,
-    user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "ClipboardChangedEve", rename_fields = "snake_case")]
+pub struct ClipboardChangedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub image: String,
+    pub session: u32,
+    pub client_info: String,
+    pub hashes: String,
+    pub archived: bool,
+    pub user: String,
 }
 
 // EVENT 25
-struct ProcessTamperingEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    image: String,
-    type_description: String,
-    user: String,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "ProcessTamperingEve", rename_fields = "snake_case")]
+pub struct ProcessTamperingEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub image: String,
+    pub type_description: String,
+    pub user: String,
 }
 
 // EVENT 26
-struct FileDeleteLoggedEvent {
-    agent_name: String,
-    agent_id: String,
-    event_action: String,
-    utc_time: DateTime<Utc>,
-    process_guid: String,
-    process_id: u32,
-    user: String,
-    image: String,
-    target_filename: String,
-    hashes: String,
-    is_executable: bool,
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "FileDeleteLoggedEve", rename_fields = "snake_case")]
+pub struct FileDeleteLoggedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub user: String,
+    pub image: String,
+    pub target_filename: String,
+    pub hashes: String,
+    pub is_executable: bool,
+}
+
+// EVENT 27
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "FileBlockExecutableEve", rename_fields = "snake_case")]
+pub struct FileBlockExecutableEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub user: String,
+    pub image: String,
+    pub target_filename: String,
+    pub hashes: String,
+}
+
+// EVENT 28
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "FileBlockShreddingEve", rename_fields = "snake_case")]
+pub struct FileBlockShreddingEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub user: String,
+    pub image: String,
+    pub target_filename: String,
+    pub hashes: String,
+    pub is_executable: bool,
+}
+
+// EVENT 29
+#[derive(Serialize, Deserialize, Debug, SimpleObject)]
+#[graphql(name = "FileExecutableDetectedEve", rename_fields = "snake_case")]
+pub struct FileExecutableDetectedEvent {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub event_action: String,
+    #[graphql(
+        skip,
+        name = "utc_time",
+        derived(name = "utc_time_text", into = "String", with = "rfc3339", owned)
+    )]
+    pub utc_time: DateTime<Utc>,
+    pub process_guid: String,
+    pub process_id: u32,
+    pub user: String,
+    pub image: String,
+    pub target_filename: String,
+    pub hashes: String,
 }
@@ -1,387 +1,446 @@
 use crate::envs::implements::EventToCSV;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-// Sysmon structs with each evnet.code
-// Process Create
-#[derive(Serialize)]
-pub struct Event1 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub image: Option<String>,
-    pub file_version: Option<String>,
-    pub description: Option<String>,
-    pub product: Option<String>,
-    pub company: Option<String>,
-    pub original_file_name: Option<String>,
-    pub command_line: Option<String>,
-    pub current_directory: Option<String>,
-    pub user: Option<String>,
-    pub logon_guid: Option<String>,
-    pub logon_id: Option<String>,
-    pub terminal_session_id: Option<String>,
-    pub integrity_level: Option<String>,
-    pub hashes: Option<String>,
-    pub parent_process_guid: Option<String>,
-    pub parent_process_id: Option<String>,
-    pub parent_image: Option<String>,
-    pub parent_command_line: Option<String>,
-    pub parent_user: Option<String>,
-}
+// Build one Sysmon struct per event.code from a field table, `EventN = code => "event.action" { ... }`
+// `field: "SysmonKey"` lines become CSV columns (header = field name) in the written order, after agent_name, agent_id, event_action, record_id
+// adding a column is one line in the table below
+macro_rules! sysmon_event {
+    ($($name:ident = $code:literal => $action:literal { $($field:ident: $key:literal),* $(,)? })*) => {
+        $(
+            #[derive(Serialize, Deserialize)]
+            pub struct $name {
+                pub agent_name: Option<String>,
+                pub agent_id: Option<String>,
+                pub event_action: Option<String>,
+                pub record_id: Option<String>,
+                $(pub $field: Option<String>,)*
+            }
 
-// File creation time changed
-#[derive(Serialize)]
-pub struct Event2 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub image: Option<String>,
-    pub target_filename: Option<String>,
-    pub creation_utc_time: Option<String>,
-    pub previous_creation_utc_time: Option<String>,
-    pub user: Option<String>,
-}
+            impl EventToCSV for $name {
+                const EVENT_CODE: u8 = $code;
 
-// Network connection detected
-#[derive(Serialize)]
-pub struct Event3 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub image: Option<String>,
-    pub user: Option<String>,
-    pub protocol: Option<String>,
-    pub initiated: Option<String>,
-    pub source_is_ipv6: Option<String>,
-    pub source_ip: Option<String>,
-    pub source_hostname: Option<String>,
-    pub source_port: Option<String>,
-    pub source_port_name: Option<String>,
-    pub destination_is_ipv6: Option<String>,
-    pub destination_ip: Option<String>,
-    pub destination_hostname: Option<String>,
-    pub destination_port: Option<String>,
-    pub destination_port_name: Option<String>,
-}
+                const EVENT_ACTION: &'static str = $action;
 
-// Event4 : Sysmon service state changed 이벤트로 추출 불필요
-
-// Process terminated
-#[derive(Serialize)]
-pub struct Event5 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub image: Option<String>,
-    pub user: Option<String>,
-}
+                const FIELDS: &'static [&'static str] = &[
+                    "agent_name",
+                    "agent_id",
+                    "event_action",
+                    "record_id",
+                    $(stringify!($field),)*
+                ];
 
-// Driver loaded
-#[derive(Serialize)]
-pub struct Event6 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub hashes: Option<String>,
-    pub signed: Option<String>,
-    pub signature: Option<String>,
-    pub signature_status: Option<String>,
-}
+                fn new(
+                    agent_name: Option<String>,
+                    agent_id: Option<String>,
+                    record_id: Option<String>,
+                ) -> Self {
+                    $name {
+                        agent_name,
+                        agent_id,
+                        event_action: Some(Self::EVENT_ACTION.to_string()),
+                        record_id,
+                        $($field: None,)*
+                    }
+                }
 
-// Image loaded
-#[derive(Serialize)]
-pub struct Event7 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub image: Option<String>,
-    pub image_loaded: Option<String>,
-    pub file_version: Option<String>,
-    pub description: Option<String>,
-    pub product: Option<String>,
-    pub company: Option<String>,
-    pub original_file_name: Option<String>,
-    pub hashes: Option<String>,
-    pub signed: Option<String>,
-    pub signature: Option<String>,
-    pub signature_status: Option<String>,
-    pub user: Option<String>,
-}
+                fn set_field(&mut self, key: &str, value: &str) {
+                    match key {
+                        $($key => self.$field = Some(value.to_string()),)*
+                        _ => {}
+                    }
+                }
 
-// CreateRemoteThread detected
-#[derive(Serialize)]
-pub struct Event8 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub source_process_guid: Option<String>,
-    pub source_process_id: Option<String>,
-    pub source_image: Option<String>,
-    pub target_image: Option<String>,
-    pub new_thread_id: Option<String>,
-    pub start_address: Option<String>,
-    pub start_module: Option<String>,
-    pub start_function: Option<String>,
-    pub source_user: Option<String>,
-    pub target_user: Option<String>,
-}
+                fn field(&self, name: &str) -> Option<&str> {
+                    match name {
+                        "agent_name" => self.agent_name.as_deref(),
+                        "agent_id" => self.agent_id.as_deref(),
+                        "event_action" => self.event_action.as_deref(),
+                        "record_id" => self.record_id.as_deref(),
+                        $(stringify!($field) => self.$field.as_deref(),)*
+                        _ => None,
+                    }
+                }
+            }
+        )*
 
-// RawAccessRead detected
-#[derive(Serialize)]
-pub struct Event9 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub image: Option<String>,
-    pub device: Option<String>,
-    pub user: Option<String>,
+        // (event.code, event.action) of every event type
+        // RocksDB has one column family per event.action, keys start with event.code
+        pub const EVENT_TYPES: &[(u8, &str)] = &[$(($code, $action),)*];
+    };
 }
 
-// Process accessed
-#[derive(Serialize)]
-pub struct Event10 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub source_process_guid: Option<String>,
-    pub source_process_id: Option<String>,
-    pub source_thread_id: Option<String>,
-    pub source_image: Option<String>,
-    pub target_process_guid: Option<String>,
-    pub target_process_id: Option<String>,
-    pub target_thread_id: Option<String>,
-    pub target_image: Option<String>,
-    pub granted_access: Option<String>,
-    pub call_trace: Option<String>,
-    pub source_user: Option<String>,
-    pub target_user: Option<String>,
-}
+// Sysmon structs with each evnet.code
+sysmon_event! {
+    // Process Create
+    Event1 = 1 => "Process Create" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        image: "Image",
+        file_version: "FileVersion",
+        description: "Description",
+        product: "Product",
+        company: "Company",
+        original_file_name: "OriginalFileName",
+        command_line: "CommandLine",
+        current_directory: "CurrentDirectory",
+        user: "User",
+        logon_guid: "LogonGuid",
+        logon_id: "LogonId",
+        terminal_session_id: "TerminalSessionId",
+        integrity_level: "IntegrityLevel",
+        hashes: "Hashes",
+        parent_process_guid: "ParentProcessGuid",
+        parent_process_id: "ParentProcessId",
+        parent_image: "ParentImage",
+        parent_command_line: "ParentCommandLine",
+        parent_user: "ParentUser",
+    }
 
-// File created
-#[derive(Serialize)]
-pub struct Event11 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub image: Option<String>,
-    pub target_filename: Option<String>,
-    pub creation_utc_time: Option<String>,
-    pub user: Option<String>,
-}
+    // File creation time changed
+    Event2 = 2 => "File creation time changed" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        image: "Image",
+        target_filename: "TargetFilename",
+        creation_utc_time: "CreationUtcTime",
+        previous_creation_utc_time: "PreviousCreationUtcTime",
+        user: "User",
+    }
 
-// Registry object added or deleted
-#[derive(Serialize)]
-pub struct Event12 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub event_type: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub image: Option<String>,
-    pub target_object: Option<String>,
-    pub user: Option<String>,
-}
+    // Network connection detected
+    Event3 = 3 => "Network connection detected" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        image: "Image",
+        user: "User",
+        protocol: "Protocol",
+        initiated: "Initiated",
+        source_is_ipv6: "SourceIsIpv6",
+        source_ip: "SourceIp",
+        source_hostname: "SourceHostname",
+        source_port: "SourcePort",
+        source_port_name: "SourcePortName",
+        destination_is_ipv6: "DestinationIsIpv6",
+        destination_ip: "DestinationIp",
+        destination_hostname: "DestinationHostname",
+        destination_port: "DestinationPort",
+        destination_port_name: "DestinationPortName",
+    }
 
-// Registry value set
-#[derive(Serialize)]
-pub struct Event13 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub event_type: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub image: Option<String>,
-    pub target_object: Option<String>,
-    pub details: Option<String>,
-    pub user: Option<String>,
-}
+    // Event4 : Sysmon service state changed 이벤트로 추출 불필요
 
-// Registry object renamed
-#[derive(Serialize)]
-pub struct Event14 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub event_type: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub image: Option<String>,
-    pub target_object: Option<String>,
-    pub new_name: Option<String>,
-    pub user: Option<String>,
-}
+    // Process terminated
+    Event5 = 5 => "Process terminated" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        image: "Image",
+        user: "User",
+    }
 
-// File stream created
-#[derive(Serialize)]
-pub struct Event15 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub image: Option<String>,
-    pub target_filename: Option<String>,
-    pub creation_utc_time: Option<String>,
-    pub hash: Option<String>,
-    pub contents: Option<String>,
-    pub user: Option<String>,
-}
+    // Driver loaded
+    Event6 = 6 => "Driver loaded" {
+        utc_time: "UtcTime",
+        image_loaded: "ImageLoaded",
+        hashes: "Hashes",
+        signed: "Signed",
+        signature: "Signature",
+        signature_status: "SignatureStatus",
+    }
 
-// Event16 : Sysmon config state changed 이벤트로 추출 불필요
-
-// Pipe Created
-#[derive(Serialize)]
-pub struct Event17 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub event_type: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub pipe_name: Option<String>,
-    pub image: Option<String>,
-    pub user: Option<String>,
-}
+    // Image loaded
+    Event7 = 7 => "Image loaded" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        image: "Image",
+        image_loaded: "ImageLoaded",
+        file_version: "FileVersion",
+        description: "Description",
+        product: "Product",
+        company: "Company",
+        original_file_name: "OriginalFileName",
+        hashes: "Hashes",
+        signed: "Signed",
+        signature: "Signature",
+        signature_status: "SignatureStatus",
+        user: "User",
+    }
 
-// Pipe Connected
-#[derive(Serialize)]
-pub struct Event18 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub event_type: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub pipe_name: Option<String>,
-    pub image: Option<String>,
-    pub user: Option<String>,
-}
+    // CreateRemoteThread detected
+    Event8 = 8 => "CreateRemoteThread detected" {
+        utc_time: "UtcTime",
+        source_process_guid: "SourceProcessGuid",
+        source_process_id: "SourceProcessId",
+        source_image: "SourceImage",
+        target_process_guid: "TargetProcessGuid",
+        target_process_id: "TargetProcessId",
+        target_image: "TargetImage",
+        new_thread_id: "NewThreadId",
+        start_address: "StartAddress",
+        start_module: "StartModule",
+        start_function: "StartFunction",
+        source_user: "SourceUser",
+        target_user: "TargetUser",
+    }
 
-// Event 19, 20, 21 이벤트 미생성
-
-// Dns query
-#[derive(Serialize)]
-pub struct Event22 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub query_name: Option<String>,
-    pub query_status: Option<String>,
-    pub query_results: Option<String>,
-    pub image: Option<String>,
-    pub user: Option<String>,
-}
+    // RawAccessRead detected
+    Event9 = 9 => "RawAccessRead detected" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        image: "Image",
+        device: "Device",
+        user: "User",
+    }
 
-// File Delete archived
-#[derive(Serialize)]
-pub struct Event23 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub user: Option<String>,
-    pub image: Option<String>,
-    pub target_filename: Option<String>,
-    pub hashes: Option<String>,
-    pub is_executable: Option<String>,
-    pub archived: Option<String>,
-}
+    // Process accessed
+    Event10 = 10 => "Process accessed" {
+        utc_time: "UtcTime",
+        source_process_guid: "SourceProcessGuid",
+        source_process_id: "SourceProcessId",
+        source_thread_id: "SourceThreadId",
+        source_image: "SourceImage",
+        target_process_guid: "TargetProcessGuid",
+        target_process_id: "TargetProcessId",
+        target_thread_id: "TargetThreadId",
+        target_image: "TargetImage",
+        granted_access: "GrantedAccess",
+        call_trace: "CallTrace",
+        source_user: "SourceUser",
+        target_user: "TargetUser",
+    }
 
-// Clipboard changed
-#[derive(Serialize)]
-pub struct Event24 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub image: Option<String>,
-    pub session: Option<String>,
-    pub client_info: Option<String>,
-    pub hashes: Option<String>,
-    pub archived: Option<String>,
-    pub user: Option<String>,
-}
+    // File created
+    Event11 = 11 => "File created" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        image: "Image",
+        target_filename: "TargetFilename",
+        creation_utc_time: "CreationUtcTime",
+        user: "User",
+    }
 
-// Process Tampering
-#[derive(Serialize)]
-pub struct Event25 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub image: Option<String>,
-    pub types: Option<String>,
-    pub user: Option<String>,
-}
+    // Registry object added or deleted
+    Event12 = 12 => "Registry object added or deleted" {
+        utc_time: "UtcTime",
+        event_type: "EventType",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        image: "Image",
+        target_object: "TargetObject",
+        user: "User",
+    }
+
+    // Registry value set
+    Event13 = 13 => "Registry value set" {
+        utc_time: "UtcTime",
+        event_type: "EventType",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        image: "Image",
+        target_object: "TargetObject",
+        details: "Details",
+        user: "User",
+    }
+
+    // Registry object renamed
+    Event14 = 14 => "Registry object renamed" {
+        utc_time: "UtcTime",
+        event_type: "EventType",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        image: "Image",
+        target_object: "TargetObject",
+        new_name: "NewName",
+        user: "User",
+    }
+
+    // File stream created
+    Event15 = 15 => "File stream created" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        image: "Image",
+        target_filename: "TargetFilename",
+        creation_utc_time: "CreationUtcTime",
+        hash: "Hash",
+        contents: "Contents",
+        user: "User",
+    }
+
+    // Sysmon config state changed
+    Event16 = 16 => "Sysmon config state changed" {
+        utc_time: "UtcTime",
+        configuration: "Configuration",
+        configuration_file_hash: "ConfigurationFileHash",
+    }
+
+    // Pipe Created
+    Event17 = 17 => "Pipe Created" {
+        utc_time: "UtcTime",
+        event_type: "EventType",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        pipe_name: "PipeName",
+        image: "Image",
+        user: "User",
+    }
+
+    // Pipe Connected
+    Event18 = 18 => "Pipe Connected" {
+        utc_time: "UtcTime",
+        event_type: "EventType",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        pipe_name: "PipeName",
+        image: "Image",
+        user: "User",
+    }
+
+    // WmiEventFilter activity detected
+    Event19 = 19 => "WmiEventFilter activity detected" {
+        utc_time: "UtcTime",
+        event_type: "EventType",
+        operation: "Operation",
+        user: "User",
+        event_namespace: "EventNamespace",
+        name: "Name",
+        query: "Query",
+    }
+
+    // WmiEventConsumer activity detected
+    Event20 = 20 => "WmiEventConsumer activity detected" {
+        utc_time: "UtcTime",
+        event_type: "EventType",
+        operation: "Operation",
+        user: "User",
+        name: "Name",
+        types: "Type",
+        destination: "Destination",
+    }
+
+    // WmiEventConsumerToFilter activity detected
+    Event21 = 21 => "WmiEventConsumerToFilter activity detected" {
+        utc_time: "UtcTime",
+        event_type: "EventType",
+        operation: "Operation",
+        user: "User",
+        consumer: "Consumer",
+        filter: "Filter",
+    }
 
-// File Delete logged
-#[derive(Serialize)]
-pub struct Event26 {
-    pub agent_name: Option<String>,
-    pub agent_id: Option<String>,
-    pub event_action: Option<String>,
-    pub utc_time: Option<String>,
-    pub process_guid: Option<String>,
-    pub process_id: Option<String>,
-    pub user: Option<String>,
-    pub image: Option<String>,
-    pub target_filename: Option<String>,
-    pub hashes: Option<String>,
-    pub is_executable: Option<String>,
+    // Dns query
+    Event22 = 22 => "Dns query" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        query_name: "QueryName",
+        query_status: "QueryStatus",
+        query_results: "QueryResults",
+        image: "Image",
+        user: "User",
+    }
+
+    // File Delete archived
+    Event23 = 23 => "File Delete archived" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        user: "User",
+        image: "Image",
+        target_filename: "TargetFilename",
+        hashes: "Hashes",
+        is_executable: "IsExecutable",
+        archived: "Archived",
+    }
+
+    // Clipboard changed
+    Event24 = 24 => "Clipboard changed" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        image: "Image",
+        session: "Session",
+        client_info: "ClientInfo",
+        hashes: "Hashes",
+        archived: "Archived",
+        user: "User",
+    }
+
+    // Process Tampering
+    Event25 = 25 => "Process Tampering" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        image: "Image",
+        types: "Type",
+        user: "User",
+    }
+
+    // File Delete logged
+    Event26 = 26 => "File Delete logged" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        user: "User",
+        image: "Image",
+        target_filename: "TargetFilename",
+        hashes: "Hashes",
+        is_executable: "IsExecutable",
+    }
+
+    // File Block Executable
+    Event27 = 27 => "File Block Executable" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        user: "User",
+        image: "Image",
+        target_filename: "TargetFilename",
+        hashes: "Hashes",
+    }
+
+    // File Block Shredding
+    Event28 = 28 => "File Block Shredding" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        user: "User",
+        image: "Image",
+        target_filename: "TargetFilename",
+        hashes: "Hashes",
+        is_executable: "IsExecutable",
+    }
+
+    // File Executable Detected
+    Event29 = 29 => "File Executable Detected" {
+        utc_time: "UtcTime",
+        process_guid: "ProcessGuid",
+        process_id: "ProcessId",
+        user: "User",
+        image: "Image",
+        target_filename: "TargetFilename",
+        hashes: "Hashes",
+    }
 }
 
 // Printout counts each events
+// error is returned so the fetcher does not move its high-water mark past rows that were not written
 #[allow(non_snake_case)]
-#[allow(dead_code)]
-pub(crate) fn process_event_data<T: EventToCSV>(
+pub fn process_event_data<T: EventToCSV>(
     data: &serde_json::Value,
     filename: &str,
     SIZE: usize,
-) {
+) -> std::io::Result<()> {
     let entries = T::parse(data);
     println!("Data counts(Max: {}): {}", SIZE, entries.len());
-    if let Err(e) = T::write_to_csv(&entries, filename) {
+    T::write_to_csv(&entries, filename).map_err(|e| {
         eprintln!("Error writing to CSV: {:?}", e);
-    }
+        e
+    })
 }
@@ -1,5 +1,6 @@
 #![allow(non_snake_case)]
 
+pub mod convert;
 pub mod detectEvent;
 pub mod eventTypes;
 pub mod events;
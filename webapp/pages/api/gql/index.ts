@@ -10,6 +10,9 @@ import {
     GraphQLQuery,
 } from "../../../app/components/types";
 
+// apollo server of graphql/ by default, `server` answers the same schema on /graphql
+const GRAPHQL_URL = process.env.GRAPHQL_URL || "http://localhost:4000/";
+
 export default async function POST(
     req: NextApiRequest,
     res: NextApiResponse<GraphQLResponse | { message: string }>
@@ -80,7 +83,7 @@ export default async function POST(
 
         try {
             const response = await axios.post(
-                "http://localhost:4000/",
+                GRAPHQL_URL,
                 JSON.stringify(graphqlQuery),
                 {
                     headers: {